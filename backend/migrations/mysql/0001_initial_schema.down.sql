@@ -0,0 +1,4 @@
+-- Drop tables in reverse order of their foreign keys
+DROP TABLE IF EXISTS tasks;
+DROP TABLE IF EXISTS projects;
+DROP TABLE IF EXISTS users;
@@ -25,4 +25,4 @@ CREATE TABLE IF NOT EXISTS tasks (
     created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
     FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
     FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
-);
\ No newline at end of file
+);
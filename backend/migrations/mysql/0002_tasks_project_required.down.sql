@@ -0,0 +1 @@
+ALTER TABLE tasks MODIFY project_id INT;
@@ -0,0 +1,11 @@
+ALTER TABLE tasks MODIFY project_id INT;
+-- Databases set up from older schema files allowed tasks without a project, none
+-- of the endpoints can reach those. They move to a new "Default" project of their owner
+INSERT INTO projects (name, user_id)
+SELECT DISTINCT 'Default', user_id FROM tasks WHERE project_id IS NULL;
+UPDATE tasks
+SET project_id = (
+    SELECT MAX(p.id) FROM projects p WHERE p.user_id = tasks.user_id AND p.name = 'Default'
+)
+WHERE project_id IS NULL;
+ALTER TABLE tasks MODIFY project_id INT NOT NULL;
@@ -0,0 +1,31 @@
+-- Drop tables in reverse order of their foreign keys
+DROP TABLE IF EXISTS saved_views;
+DROP TABLE IF EXISTS task_activity;
+DROP TABLE IF EXISTS task_labels;
+DROP TABLE IF EXISTS time_entries;
+DROP TABLE IF EXISTS checklist_items;
+DROP TABLE IF EXISTS task_attachments;
+DROP TABLE IF EXISTS task_comments;
+DROP TABLE IF EXISTS task_dependencies;
+ALTER TABLE tasks
+    DROP FOREIGN KEY tasks_parent_id_fk,
+    DROP FOREIGN KEY tasks_next_occurrence_id_fk;
+-- tasks_board shrinks to (project_id) with its columns, it may back the project foreign key
+ALTER TABLE tasks
+    DROP INDEX tasks_search,
+    DROP INDEX tasks_deleted_at,
+    DROP COLUMN deleted_at,
+    DROP COLUMN board_rank,
+    DROP COLUMN estimate_hours,
+    DROP COLUMN story_points,
+    DROP COLUMN next_occurrence_id,
+    DROP COLUMN occurrence,
+    DROP COLUMN recurrence,
+    DROP COLUMN priority,
+    DROP COLUMN status,
+    DROP COLUMN parent_id;
+ALTER TABLE projects
+    DROP INDEX projects_search,
+    DROP INDEX projects_deleted_at,
+    DROP COLUMN deleted_at,
+    DROP COLUMN archived_at;
@@ -0,0 +1,127 @@
+-- Projects can be archived and moved to the trash
+ALTER TABLE projects
+    ADD COLUMN archived_at DATETIME AFTER created_at,
+    ADD COLUMN deleted_at DATETIME AFTER archived_at,
+    ADD INDEX projects_deleted_at (deleted_at);
+-- Subtasks, board columns and ranks, priorities, recurrence, estimates and the trash
+ALTER TABLE tasks
+    ADD COLUMN parent_id INT AFTER project_id,
+    ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'todo' AFTER description,
+    ADD COLUMN priority VARCHAR(20) NOT NULL DEFAULT 'medium' AFTER status,
+    ADD COLUMN recurrence VARCHAR(255) AFTER due_date,
+    ADD COLUMN occurrence INT NOT NULL DEFAULT 1 AFTER recurrence,
+    ADD COLUMN next_occurrence_id INT AFTER occurrence,
+    ADD COLUMN story_points INT AFTER next_occurrence_id,
+    ADD COLUMN estimate_hours DOUBLE AFTER story_points,
+    ADD COLUMN board_rank VARCHAR(255) CHARACTER SET ascii COLLATE ascii_bin NOT NULL DEFAULT 'i' AFTER estimate_hours,
+    ADD COLUMN deleted_at DATETIME AFTER created_at,
+    ADD INDEX tasks_board (project_id, status, board_rank),
+    ADD INDEX tasks_deleted_at (deleted_at),
+    ADD CONSTRAINT tasks_parent_id_fk FOREIGN KEY (parent_id) REFERENCES tasks(id) ON DELETE SET NULL,
+    ADD CONSTRAINT tasks_next_occurrence_id_fk FOREIGN KEY (next_occurrence_id) REFERENCES tasks(id) ON DELETE SET NULL;
+-- Full-text search, InnoDB builds one FULLTEXT index per statement
+ALTER TABLE projects ADD FULLTEXT INDEX projects_search (name);
+ALTER TABLE tasks ADD FULLTEXT INDEX tasks_search (title, description);
+-- Create task dependencies table (task_id cannot be done before depends_on_id)
+CREATE TABLE IF NOT EXISTS task_dependencies (
+    task_id INT NOT NULL,
+    depends_on_id INT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (task_id, depends_on_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (depends_on_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+-- Create task comments table (deleted_at keeps soft-deleted comments in their thread)
+CREATE TABLE IF NOT EXISTS task_comments (
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    task_id INT NOT NULL,
+    user_id INT NOT NULL,
+    parent_id INT,
+    body TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    edited_at DATETIME,
+    deleted_at DATETIME,
+    FULLTEXT (body),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_id) REFERENCES task_comments(id) ON DELETE CASCADE
+);
+-- Create task attachments table (sha256 is the key of the content in the storage backend)
+CREATE TABLE IF NOT EXISTS task_attachments (
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    task_id INT NOT NULL,
+    user_id INT NOT NULL,
+    filename VARCHAR(255) NOT NULL,
+    content_type VARCHAR(255) NOT NULL,
+    size BIGINT NOT NULL,
+    sha256 CHAR(64) NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX (sha256),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+-- Create checklist items table
+CREATE TABLE IF NOT EXISTS checklist_items (
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    task_id INT NOT NULL,
+    text VARCHAR(255) NOT NULL,
+    checked BOOLEAN NOT NULL DEFAULT FALSE,
+    position INT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+-- Create time entries table (running_user_id is only set while a timer runs, one per user)
+CREATE TABLE IF NOT EXISTS time_entries (
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    task_id INT NOT NULL,
+    user_id INT NOT NULL,
+    started_at DATETIME NOT NULL,
+    ended_at DATETIME,
+    note TEXT,
+    running_user_id INT AS (IF(ended_at IS NULL, user_id, NULL)) VIRTUAL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY (running_user_id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+-- Create task labels table
+CREATE TABLE IF NOT EXISTS task_labels (
+    task_id INT NOT NULL,
+    label VARCHAR(50) NOT NULL,
+    PRIMARY KEY (task_id, label),
+    INDEX (label),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+-- Create task activity table (append-only, kept after the task itself is deleted)
+CREATE TABLE IF NOT EXISTS task_activity (
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    task_id INT NOT NULL,
+    project_id INT NOT NULL,
+    user_id INT NOT NULL,
+    action VARCHAR(20) NOT NULL,
+    field VARCHAR(50),
+    old_value TEXT,
+    new_value TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX (task_id, id),
+    INDEX (project_id, id),
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+-- Create saved views table (query uses the task filter language, a project_id shares the view within that project)
+CREATE TABLE IF NOT EXISTS saved_views (
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    user_id INT NOT NULL,
+    project_id INT,
+    name VARCHAR(255) NOT NULL,
+    query VARCHAR(1000) NOT NULL DEFAULT '',
+    sort VARCHAR(20) NOT NULL DEFAULT 'created',
+    descending BOOLEAN NOT NULL DEFAULT FALSE,
+    display VARCHAR(20) NOT NULL DEFAULT 'list',
+    include_archived BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    INDEX (user_id),
+    INDEX (project_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+-- The original case of the addresses is lost, nothing to restore
+DO 0;
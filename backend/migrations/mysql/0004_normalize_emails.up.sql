@@ -0,0 +1,11 @@
+-- Emails are now stored trimmed and lowercased. The default collation already
+-- compares them case-insensitively, so the UNIQUE key needs no change.
+-- Addresses that only differed by surrounding whitespace (or, with a binary
+-- collation, by case) would collide: the oldest account keeps the address, the
+-- others get it suffixed with `#<id>` and can no longer sign in with it until
+-- it is sorted out by hand
+UPDATE users SET email = CONCAT(LEFT(LOWER(TRIM(email)), 240), '#', id)
+WHERE id NOT IN (
+    SELECT kept.id FROM (SELECT MIN(id) AS id FROM users GROUP BY LOWER(TRIM(email))) AS kept
+);
+UPDATE users SET email = LOWER(TRIM(email));
@@ -0,0 +1,13 @@
+-- Sign-ups waiting for the emailed token in SIGNUP_MODE=email, the account is
+-- only created once the address is confirmed. `password` is already hashed,
+-- the token is stored as its SHA-256
+CREATE TABLE IF NOT EXISTS pending_signups (
+    id INT PRIMARY KEY AUTO_INCREMENT,
+    email VARCHAR(255) NOT NULL,
+    username VARCHAR(255) NOT NULL,
+    password VARCHAR(255) NOT NULL,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    created_at DATETIME NOT NULL,
+    INDEX (email),
+    INDEX (created_at)
+);
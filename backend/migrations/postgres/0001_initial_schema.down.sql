@@ -0,0 +1,12 @@
+-- Drop tables in reverse order of their foreign keys
+DROP TABLE IF EXISTS saved_views;
+DROP TABLE IF EXISTS task_activity;
+DROP TABLE IF EXISTS task_labels;
+DROP TABLE IF EXISTS time_entries;
+DROP TABLE IF EXISTS checklist_items;
+DROP TABLE IF EXISTS task_attachments;
+DROP TABLE IF EXISTS task_comments;
+DROP TABLE IF EXISTS task_dependencies;
+DROP TABLE IF EXISTS tasks;
+DROP TABLE IF EXISTS projects;
+DROP TABLE IF EXISTS users;
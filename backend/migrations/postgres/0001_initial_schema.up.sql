@@ -0,0 +1,128 @@
+-- Same schema as the MySQL migrations, timestamps are stored without time zone in UTC
+-- Create users table
+CREATE TABLE IF NOT EXISTS users (
+    id BIGSERIAL PRIMARY KEY,
+    username VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL UNIQUE,
+    password VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc')
+);
+-- Create projects table
+CREATE TABLE IF NOT EXISTS projects (
+    id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc'),
+    archived_at TIMESTAMP,
+    deleted_at TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS projects_deleted_at ON projects (deleted_at);
+-- Create tasks table (board_rank compares bytewise, like ascii_bin on MySQL)
+CREATE TABLE IF NOT EXISTS tasks (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    project_id BIGINT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    parent_id BIGINT REFERENCES tasks(id) ON DELETE SET NULL,
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    status VARCHAR(20) NOT NULL DEFAULT 'todo',
+    priority VARCHAR(20) NOT NULL DEFAULT 'medium',
+    due_date TIMESTAMP,
+    recurrence VARCHAR(255),
+    occurrence INTEGER NOT NULL DEFAULT 1,
+    next_occurrence_id BIGINT REFERENCES tasks(id) ON DELETE SET NULL,
+    story_points BIGINT,
+    estimate_hours DOUBLE PRECISION,
+    board_rank VARCHAR(255) COLLATE "C" NOT NULL DEFAULT 'i',
+    created_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc'),
+    deleted_at TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS tasks_board ON tasks (project_id, status, board_rank);
+CREATE INDEX IF NOT EXISTS tasks_parent_id ON tasks (parent_id);
+CREATE INDEX IF NOT EXISTS tasks_deleted_at ON tasks (deleted_at);
+-- Create task dependencies table (task_id cannot be done before depends_on_id)
+CREATE TABLE IF NOT EXISTS task_dependencies (
+    task_id BIGINT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    depends_on_id BIGINT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc'),
+    PRIMARY KEY (task_id, depends_on_id)
+);
+-- Create task comments table (deleted_at keeps soft-deleted comments in their thread)
+CREATE TABLE IF NOT EXISTS task_comments (
+    id BIGSERIAL PRIMARY KEY,
+    task_id BIGINT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    parent_id BIGINT REFERENCES task_comments(id) ON DELETE CASCADE,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc'),
+    edited_at TIMESTAMP,
+    deleted_at TIMESTAMP
+);
+-- Create task attachments table (sha256 is the key of the content in the storage backend)
+CREATE TABLE IF NOT EXISTS task_attachments (
+    id BIGSERIAL PRIMARY KEY,
+    task_id BIGINT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    filename VARCHAR(255) NOT NULL,
+    content_type VARCHAR(255) NOT NULL,
+    size BIGINT NOT NULL,
+    sha256 CHAR(64) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc')
+);
+CREATE INDEX IF NOT EXISTS task_attachments_sha256 ON task_attachments (sha256);
+-- Create checklist items table
+CREATE TABLE IF NOT EXISTS checklist_items (
+    id BIGSERIAL PRIMARY KEY,
+    task_id BIGINT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    text VARCHAR(255) NOT NULL,
+    checked BOOLEAN NOT NULL DEFAULT FALSE,
+    position INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc')
+);
+-- Create time entries table (at most one running timer per user)
+CREATE TABLE IF NOT EXISTS time_entries (
+    id BIGSERIAL PRIMARY KEY,
+    task_id BIGINT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    started_at TIMESTAMP NOT NULL,
+    ended_at TIMESTAMP,
+    note TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc')
+);
+CREATE UNIQUE INDEX IF NOT EXISTS time_entries_running ON time_entries (user_id) WHERE ended_at IS NULL;
+-- Create task labels table
+CREATE TABLE IF NOT EXISTS task_labels (
+    task_id BIGINT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    label VARCHAR(50) NOT NULL,
+    PRIMARY KEY (task_id, label)
+);
+CREATE INDEX IF NOT EXISTS task_labels_label ON task_labels (label);
+-- Create task activity table (append-only, kept after the task itself is deleted)
+CREATE TABLE IF NOT EXISTS task_activity (
+    id BIGSERIAL PRIMARY KEY,
+    task_id BIGINT NOT NULL,
+    project_id BIGINT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    action VARCHAR(20) NOT NULL,
+    field VARCHAR(50),
+    old_value TEXT,
+    new_value TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc')
+);
+CREATE INDEX IF NOT EXISTS task_activity_task ON task_activity (task_id, id);
+CREATE INDEX IF NOT EXISTS task_activity_project ON task_activity (project_id, id);
+-- Create saved views table (query uses the task filter language, a project_id shares the view within that project)
+CREATE TABLE IF NOT EXISTS saved_views (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    project_id BIGINT REFERENCES projects(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    query VARCHAR(1000) NOT NULL DEFAULT '',
+    sort VARCHAR(20) NOT NULL DEFAULT 'created',
+    descending BOOLEAN NOT NULL DEFAULT FALSE,
+    display VARCHAR(20) NOT NULL DEFAULT 'list',
+    include_archived BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT (now() AT TIME ZONE 'utc')
+);
+CREATE INDEX IF NOT EXISTS saved_views_user_id ON saved_views (user_id);
+CREATE INDEX IF NOT EXISTS saved_views_project_id ON saved_views (project_id);
@@ -0,0 +1,3 @@
+ALTER TABLE tasks DROP COLUMN deletion_id;
+ALTER TABLE projects DROP COLUMN deletion_id;
+DROP TABLE IF EXISTS deletions;
@@ -0,0 +1,23 @@
+-- Everything one request moves to the trash shares a deletion id, restoring
+-- brings back what went with it instead of whatever has the same timestamp
+CREATE TABLE IF NOT EXISTS deletions (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    deleted_at TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS deletions_deleted_at ON deletions (deleted_at);
+ALTER TABLE projects ADD COLUMN deletion_id BIGINT;
+ALTER TABLE tasks ADD COLUMN deletion_id BIGINT;
+-- Items already in the trash were grouped by their timestamp
+INSERT INTO deletions (user_id, deleted_at)
+SELECT user_id, deleted_at FROM projects WHERE deleted_at IS NOT NULL
+UNION
+SELECT user_id, deleted_at FROM tasks WHERE deleted_at IS NOT NULL;
+UPDATE projects SET deletion_id = (
+    SELECT d.id FROM deletions d WHERE d.user_id = projects.user_id AND d.deleted_at = projects.deleted_at
+)
+WHERE deleted_at IS NOT NULL;
+UPDATE tasks SET deletion_id = (
+    SELECT d.id FROM deletions d WHERE d.user_id = tasks.user_id AND d.deleted_at = tasks.deleted_at
+)
+WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,13 @@
+-- Sign-ups waiting for the emailed token in SIGNUP_MODE=email, the account is
+-- only created once the address is confirmed. `password` is already hashed,
+-- the token is stored as its SHA-256
+CREATE TABLE IF NOT EXISTS pending_signups (
+    id BIGSERIAL PRIMARY KEY,
+    email VARCHAR(255) NOT NULL,
+    username VARCHAR(255) NOT NULL,
+    password VARCHAR(255) NOT NULL,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS pending_signups_email ON pending_signups (email);
+CREATE INDEX IF NOT EXISTS pending_signups_created_at ON pending_signups (created_at);
@@ -0,0 +1,8 @@
+-- Emails are now stored trimmed and lowercased, the index keeps addresses that
+-- only differ by case out. Addresses that would collide once normalized: the
+-- oldest account keeps the address, the others get it suffixed with `#<id>` and
+-- can no longer sign in with it until it is sorted out by hand
+UPDATE users SET email = substr(lower(trim(email)), 1, 240) || '#' || id
+WHERE id NOT IN (SELECT MIN(id) FROM users GROUP BY lower(trim(email)));
+UPDATE users SET email = lower(trim(email));
+CREATE UNIQUE INDEX users_email_nocase ON users (email COLLATE NOCASE);
@@ -0,0 +1,6 @@
+-- One row per stored attachment content, locked while attachments sharing it are
+-- created or deleted so the content is never removed under a new attachment.
+-- Rows are created on first use
+CREATE TABLE IF NOT EXISTS attachment_blobs (
+    sha256 TEXT PRIMARY KEY
+);
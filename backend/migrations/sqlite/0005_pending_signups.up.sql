@@ -0,0 +1,13 @@
+-- Sign-ups waiting for the emailed token in SIGNUP_MODE=email, the account is
+-- only created once the address is confirmed. `password` is already hashed,
+-- the token is stored as its SHA-256
+CREATE TABLE IF NOT EXISTS pending_signups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    email TEXT NOT NULL,
+    username TEXT NOT NULL,
+    password TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS pending_signups_email ON pending_signups (email);
+CREATE INDEX IF NOT EXISTS pending_signups_created_at ON pending_signups (created_at);
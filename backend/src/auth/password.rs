@@ -3,6 +3,11 @@ use argon2::{
     Argon2, PasswordHash, PasswordVerifier,
 };
 
+/// Verified instead of a real hash when nobody has the email, so a sign-in takes
+/// as long whether the account exists or not. Uses the default parameters.
+pub const DUMMY_HASH: &str =
+    "$argon2id$v=19$m=19456,t=2,p=1$dCcC2McTSLtXjp8/oTKggQ$Vd3Suvadg8Bmfwv4NZd3Y9qhelGaPR44twcDykn5Cjk";
+
 pub fn verify_password(password: &str, password_hash: &str) -> bool {
     let password_hash = PasswordHash::new(password_hash).expect("faild to parse password hash");
     Argon2::default()
@@ -0,0 +1,64 @@
+use std::env;
+
+use chrono::Duration;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{mail::Email, models::user::User};
+
+/// How long the token of a pending sign-up stays valid.
+pub const CONFIRMATION_TTL: Duration = Duration::hours(24);
+
+/// How sign-up answers, `SIGNUP_MODE=email` keeps it from telling which emails
+/// have an account.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SignupMode {
+    /// Signs the new user in right away, a taken email is a 409.
+    Direct,
+    /// Always answers 202, the outcome goes to the address by email. The
+    /// account is only created once the emailed token comes back.
+    Email,
+}
+
+impl SignupMode {
+    pub fn from_env() -> Self {
+        match env::var("SIGNUP_MODE").as_deref() {
+            Ok("email") => SignupMode::Email,
+            _ => SignupMode::Direct,
+        }
+    }
+}
+
+/// A random token for a pending sign-up, only its hash is stored.
+pub fn confirmation_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+pub fn token_hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+pub fn confirmation_email(email: &str, username: &str, token: &str) -> Email {
+    Email {
+        to: email.to_string(),
+        subject: "Confirm your TMS account".to_string(),
+        body: format!(
+            "Hi {},\n\nto finish signing up, confirm this email address with the token below \
+             within 24 hours. If you did not sign up, you can ignore this email.\n\n{}",
+            username, token
+        ),
+    }
+}
+
+/// Sent instead of the welcome email when someone signs up with a taken address.
+pub fn existing_account_email(user: &User) -> Email {
+    Email {
+        to: user.email.clone(),
+        subject: "Sign-up attempt on TMS".to_string(),
+        body: format!(
+            "Hi {},\n\nsomeone tried to create an account with this email address, which already has one. \
+             If it was you, sign in instead. Otherwise you can ignore this email.",
+            user.username
+        ),
+    }
+}
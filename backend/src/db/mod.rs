@@ -1,9 +1,108 @@
-use std::env;
-
-use sqlx::{MySql, Pool};
+use std::{env, str::FromStr, time::Duration};
 
+use log::LevelFilter;
+use sqlx::{
+    migrate::Migrator,
+    mysql::MySqlConnectOptions,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    ConnectOptions, MySql, Pool, Sqlite,
+};
 pub type DB = Pool<MySql>;
-pub async fn db_connection() -> DB {
+pub type SqliteDB = Pool<Sqlite>;
+#[cfg(feature = "postgres")]
+pub type PgDB = Pool<sqlx::Postgres>;
+
+/// Schema migrations from `backend/migrations`, one set per database, embedded at compile time.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations/mysql");
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+#[cfg(feature = "postgres")]
+pub static PG_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+/// The database behind `DATABASE_URL`, picked by its scheme.
+pub enum Database {
+    MySql(DB),
+    /// A single file or, with `sqlite::memory:`, a database living as long as the process.
+    Sqlite(SqliteDB),
+    #[cfg(feature = "postgres")]
+    Postgres(PgDB),
+}
+
+/// Every statement is logged with its duration at debug level on the `sqlx::query`
+/// target, the ones slower than `SLOW_QUERY_MS` (default 500) at warn level.
+fn with_query_logging<O: ConnectOptions>(options: O) -> O {
+    let slow_ms = env::var("SLOW_QUERY_MS")
+        .ok()
+        .and_then(|ms| ms.parse().ok())
+        .unwrap_or(500);
+    options
+        .log_statements(LevelFilter::Debug)
+        .log_slow_statements(LevelFilter::Warn, Duration::from_millis(slow_ms))
+}
+
+/// Connects to `DATABASE_URL` and applies pending migrations unless
+/// `RUN_MIGRATIONS=false`. Startup fails when an applied migration was edited
+/// after the fact (checksum mismatch) or is unknown to this build.
+pub async fn db_connection() -> Database {
     let url = env::var("DATABASE_URL").expect("please provide a DATABASEURL in .env file");
-    sqlx::MySqlPool::connect(&url).await.unwrap()
+    let run_migrations = env::var("RUN_MIGRATIONS")
+        .ok()
+        .and_then(|value| value.parse::<bool>().ok())
+        .unwrap_or(true);
+
+    if url.starts_with("sqlite:") {
+        let options = SqliteConnectOptions::from_str(&url)
+            .expect("Error: invalid sqlite DATABASE_URL")
+            .create_if_missing(true);
+        let options = with_query_logging(options);
+        // Every connection to `:memory:` opens its own empty database, keep a single one around
+        let in_memory = url.contains(":memory:") || url.contains("mode=memory");
+        let pool_options = if in_memory {
+            SqlitePoolOptions::new()
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+        } else {
+            SqlitePoolOptions::new()
+        };
+        let pool = pool_options.connect_with(options).await.unwrap();
+        if run_migrations {
+            SQLITE_MIGRATOR
+                .run(&pool)
+                .await
+                .expect("Error: could not apply database migrations");
+        }
+        return Database::Sqlite(pool);
+    }
+
+    if url.starts_with("postgres:") || url.starts_with("postgresql:") {
+        #[cfg(feature = "postgres")]
+        {
+            let options = sqlx::postgres::PgConnectOptions::from_str(&url)
+                .expect("Error: invalid postgres DATABASE_URL");
+            let pool = sqlx::PgPool::connect_with(with_query_logging(options))
+                .await
+                .unwrap();
+            if run_migrations {
+                PG_MIGRATOR
+                    .run(&pool)
+                    .await
+                    .expect("Error: could not apply database migrations");
+            }
+            return Database::Postgres(pool);
+        }
+        #[cfg(not(feature = "postgres"))]
+        panic!("Error: DATABASE_URL points to Postgres, build with `--features postgres`");
+    }
+
+    let options = MySqlConnectOptions::from_str(&url).expect("Error: invalid mysql DATABASE_URL");
+    let pool = sqlx::MySqlPool::connect_with(with_query_logging(options))
+        .await
+        .unwrap();
+    if run_migrations {
+        MIGRATOR
+            .run(&pool)
+            .await
+            .expect("Error: could not apply database migrations");
+    }
+    Database::MySql(pool)
 }
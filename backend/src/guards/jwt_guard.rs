@@ -19,6 +19,8 @@ impl<'r> FromRequest<'r> for JwtAuth {
 
             // Verify the JWT and extract claims
             if let Ok(decoded) = verify_jwt(token) {
+                // Guards run inside the request span, see `telemetry::request::traced`
+                tracing::Span::current().record("user_id", decoded.claims.sub.as_str());
                 return Outcome::Success(JwtAuth {
                     claims: decoded.claims,
                 });
@@ -1,2 +1,4 @@
 pub mod jwt_guard;
+pub mod request_id;
 pub mod role_guard;
+pub mod valid;
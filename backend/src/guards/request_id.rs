@@ -0,0 +1,27 @@
+use rocket::Request;
+use uuid::Uuid;
+
+/// Identifies a request in error responses and logs. A client supplied
+/// `X-Request-Id` is kept when it is short and printable, otherwise one is generated.
+#[derive(Debug)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    pub fn of<'r>(request: &'r Request<'_>) -> &'r RequestId {
+        request.local_cache(|| {
+            let id = request
+                .headers()
+                .get_one("X-Request-Id")
+                .filter(|id| {
+                    !id.is_empty()
+                        && id.len() <= 64
+                        && id
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                })
+                .map(str::to_string)
+                .unwrap_or_else(|| Uuid::new_v4().simple().to_string());
+            RequestId(id)
+        })
+    }
+}
@@ -5,6 +5,7 @@ use rocket::Request;
 
 use super::jwt_guard::JwtAuth;
 
+#[allow(dead_code)]
 pub struct RoleAuth {
     claims: Claims,
 }
@@ -0,0 +1,58 @@
+use std::ops::Deref;
+
+use rocket::{
+    data::{self, Data, FromData},
+    http::Status,
+    outcome::Outcome,
+    serde::json::Json,
+    Request,
+};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    models::error::{AppError, FieldError},
+    utils::validation::{Validate, Validator},
+};
+
+/// A JSON body that passed its `Validate` checks, already trimmed. Invalid bodies are
+/// answered with a 422 listing every bad field before the handler runs.
+pub struct Valid<T>(pub T);
+
+/// The field errors of a rejected body, left for the catcher to render.
+pub struct RejectedFields(pub Vec<FieldError>);
+
+impl<T> Valid<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Valid<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: DeserializeOwned + Validate> FromData<'r> for Valid<T> {
+    type Error = AppError;
+
+    async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> data::Outcome<'r, Self> {
+        let mut value = match Json::<T>::from_data(request, data).await {
+            Outcome::Success(json) => json.into_inner(),
+            Outcome::Error((status, _)) => return Outcome::Error((status, AppError::Http(status))),
+            Outcome::Forward(forward) => return Outcome::Forward(forward),
+        };
+
+        let mut validator = Validator::default();
+        value.validate(&mut validator);
+        let errors = validator.into_errors();
+        if errors.is_empty() {
+            return Outcome::Success(Valid(value));
+        }
+        request.local_cache(|| RejectedFields(errors.clone()));
+        Outcome::Error((Status::UnprocessableEntity, AppError::Validation(errors)))
+    }
+}
@@ -0,0 +1,37 @@
+use rocket::serde::json::Json;
+
+use crate::{
+    guards::jwt_guard::JwtAuth,
+    handlers::{project_handler::fetch_project, task_handler::fetch_task},
+    models::{activity::ActivityPage, error::AppError, pagination::Pagination},
+    repository::{ActivityRepo, ProjectRepo, TaskRepo},
+    utils::parse_user_id,
+};
+
+#[get("/<task_id>/history?<pagination..>")]
+pub async fn get_task_history(
+    tasks: &rocket::State<TaskRepo>,
+    activity: &rocket::State<ActivityRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    pagination: Pagination,
+) -> Result<Json<ActivityPage>, AppError> {
+    let user_id = parse_user_id(user)?;
+    fetch_task(tasks.inner(), task_id, user_id).await?;
+    Ok(Json(activity.task_history(task_id, &pagination).await?))
+}
+
+/// Activity of every task in the project, including the tasks deleted from it
+/// and the ones moved out of it.
+#[get("/<id>/activity?<pagination..>")]
+pub async fn get_project_activity(
+    projects: &rocket::State<ProjectRepo>,
+    activity: &rocket::State<ActivityRepo>,
+    user: JwtAuth,
+    id: i64,
+    pagination: Pagination,
+) -> Result<Json<ActivityPage>, AppError> {
+    let user_id = parse_user_id(user)?;
+    fetch_project(projects.inner(), id, user_id).await?;
+    Ok(Json(activity.project_activity(id, &pagination).await?))
+}
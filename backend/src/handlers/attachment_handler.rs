@@ -0,0 +1,156 @@
+use chrono::Utc;
+use rocket::{
+    data::ToByteUnit, http::ContentType, response::status::NoContent, serde::json::Json, Data,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    guards::jwt_guard::JwtAuth,
+    handlers::{project_handler::ensure_writable, task_handler::fetch_task},
+    models::{
+        attachment::{Attachment, AttachmentDownload},
+        error::AppError,
+    },
+    repository::{AttachmentRepo, ProjectRepo, TaskRepo},
+    storage::{AttachmentLimits, FileStorage},
+    utils::parse_user_id,
+};
+
+const ATTACHMENT_NOT_FOUND: &str = "Attachment not found";
+
+fn storage_error() -> AppError {
+    AppError::Internal("Storage Error")
+}
+
+/// Keeps the last path component and drops characters that would break the
+/// `Content-Disposition` header.
+fn sanitize_filename(filename: &str) -> String {
+    let name = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    let name: String = name
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(255)
+        .collect();
+    if name.trim().is_empty() {
+        "attachment".to_string()
+    } else {
+        name
+    }
+}
+
+#[get("/<task_id>/attachments")]
+pub async fn get_attachments(
+    tasks: &rocket::State<TaskRepo>,
+    attachments: &rocket::State<AttachmentRepo>,
+    user: JwtAuth,
+    task_id: i64,
+) -> Result<Json<Vec<Attachment>>, AppError> {
+    let user_id = parse_user_id(user)?;
+    fetch_task(tasks.inner(), task_id, user_id).await?;
+
+    let attachments = attachments.list(task_id).await?;
+    Ok(Json(attachments))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[post("/<task_id>/attachments?<filename>", data = "<data>")]
+pub async fn upload_attachment(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    attachments: &rocket::State<AttachmentRepo>,
+    limits: &rocket::State<AttachmentLimits>,
+    user: JwtAuth,
+    content_type: &ContentType,
+    task_id: i64,
+    filename: &str,
+    data: Data<'_>,
+) -> Result<Json<Attachment>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    let mime = format!("{}/{}", content_type.top(), content_type.sub()).to_ascii_lowercase();
+    if !limits.allowed_types.contains(&mime) {
+        return Err(AppError::UnsupportedMediaType("File type not allowed"));
+    }
+
+    let bytes = data
+        .open(limits.max_bytes.bytes())
+        .into_bytes()
+        .await
+        .map_err(|_| storage_error())?;
+    if !bytes.is_complete() {
+        return Err(AppError::PayloadTooLarge("File is too large"));
+    }
+    let bytes = bytes.into_inner();
+
+    let sha256 = hex::encode(Sha256::digest(&bytes));
+    let filename = sanitize_filename(filename);
+    let attachment = attachments
+        .create(
+            Attachment {
+                id: 0,
+                task_id,
+                user_id,
+                filename,
+                content_type: mime,
+                size: bytes.len() as i64,
+                sha256,
+                created_at: Utc::now(),
+            },
+            &bytes,
+        )
+        .await?;
+    Ok(Json(attachment))
+}
+
+#[get("/<task_id>/attachments/<id>")]
+pub async fn download_attachment(
+    tasks: &rocket::State<TaskRepo>,
+    attachments: &rocket::State<AttachmentRepo>,
+    storage: &rocket::State<FileStorage>,
+    user: JwtAuth,
+    task_id: i64,
+    id: i64,
+) -> Result<AttachmentDownload, AppError> {
+    let user_id = parse_user_id(user)?;
+    fetch_task(tasks.inner(), task_id, user_id).await?;
+
+    let record = attachments
+        .find(id, task_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, ATTACHMENT_NOT_FOUND))?;
+
+    let reader = storage
+        .open(&record.sha256)
+        .await
+        .map_err(|_| storage_error())?;
+    Ok(AttachmentDownload {
+        reader,
+        content_type: ContentType::parse_flexible(&record.content_type)
+            .unwrap_or(ContentType::Binary),
+        filename: record.filename,
+    })
+}
+
+#[delete("/<task_id>/attachments/<id>")]
+pub async fn delete_attachment(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    attachments: &rocket::State<AttachmentRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    id: i64,
+) -> Result<NoContent, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    // The content goes away with the last attachment pointing at it
+    attachments
+        .delete(id, task_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, ATTACHMENT_NOT_FOUND))?;
+
+    Ok(NoContent)
+}
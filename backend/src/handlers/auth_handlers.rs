@@ -1,135 +1,172 @@
+use chrono::Utc;
 use rocket::{
-    http::{Cookie, CookieJar, Status},
+    http::{Cookie, CookieJar},
     response::status,
     serde::json::Json,
+    Either,
 };
-use sqlx::MySqlPool;
+use tracing::Instrument;
 
 use crate::{
     auth::{
         jwt::generate_jwt,
-        password::{hash_password, verify_password},
+        password::{hash_password, verify_password, DUMMY_HASH},
+        signup::{
+            confirmation_email, confirmation_token, existing_account_email, token_hash, SignupMode,
+            CONFIRMATION_TTL,
+        },
     },
-    db::DB,
+    guards::valid::Valid,
+    mail::MailSender,
     models::{
-        error::ErrorResponse,
-        user::{NewUser, User, UserCredential},
+        error::AppError,
+        user::{NewUser, SignUpAccepted, SignUpConfirmation, User, UserCredential},
     },
+    repository::{PendingSignup, RepoError, UserRepo},
+    telemetry::metrics::Metrics,
 };
 
+fn email_taken() -> AppError {
+    AppError::Conflict("email_taken", "An account with this email already exists")
+}
+
+/// In `SignupMode::Email` every request gets the same 202 and the result is
+/// emailed, so the response does not reveal whether the address has an account.
+/// A new address gets a token that creates the account through `/confirm`.
 #[post("/sign-up", data = "<new_user>")]
-pub async fn sign_up<'a>(
-    db_pool: &rocket::State<DB>,
+pub async fn sign_up(
+    users: &rocket::State<UserRepo>,
+    mode: &rocket::State<SignupMode>,
+    mailer: &rocket::State<MailSender>,
+    metrics: &rocket::State<Metrics>,
     cookies: &CookieJar<'_>,
-    new_user: Json<NewUser>,
-) -> Result<Json<User>, status::Custom<Json<ErrorResponse<'a>>>> {
-    let password = hash_password(&new_user.password).map_err(|_| {
-        status::Custom(
-            Status::InternalServerError,
-            Json(ErrorResponse {
-                error: "failed to hash the password",
-            }),
-        )
-    })?;
-    let result = sqlx::query!(
-        "INSERT INTO users (email, username, password) VALUES ( ?, ?, ?)",
-        new_user.email,
-        new_user.username,
-        password
-    )
-    .execute(db_pool.inner())
-    .await
-    .map_err(|e| {
-        println!("{:?}", e);
-        status::Custom(
-            Status::InternalServerError,
-            Json(ErrorResponse {
-                error: "Database Error",
-            }),
-        )
-    })?;
+    new_user: Valid<NewUser>,
+) -> Result<Either<Json<User>, status::Accepted<Json<SignUpAccepted>>>, AppError> {
+    // Hashed up front so both outcomes take about as long
+    let password = hash_password(&new_user.password)
+        .map_err(|_| AppError::Internal("failed to hash the password"))?;
+    let existing = users.find_by_email(&new_user.email).await?;
 
-    let user = User {
-        id: result.last_insert_id() as i64,
-        username: new_user.username.clone(),
-        email: new_user.email.clone(),
-        role: "user".to_string(),
-    };
+    if **mode == SignupMode::Email {
+        let email = match existing {
+            Some(account) => existing_account_email(&account.user),
+            None => {
+                let token = confirmation_token();
+                let signup = PendingSignup {
+                    email: new_user.email.clone(),
+                    username: new_user.username.clone(),
+                    password,
+                    token_hash: token_hash(&token),
+                };
+                users
+                    .create_pending(&signup, Utc::now() - CONFIRMATION_TTL)
+                    .await?;
+                confirmation_email(&signup.email, &signup.username, &token)
+            }
+        };
+        let mailer = mailer.inner().clone();
+        rocket::tokio::spawn(
+            async move {
+                if let Err(e) = mailer.send(email).await {
+                    tracing::error!(error = %e, "failed to send the sign-up email");
+                }
+            }
+            .in_current_span(),
+        );
+        return Ok(Either::Right(accepted()));
+    }
 
-    let token = generate_jwt(&user.id.to_string(), &user.role).map_err(|_| {
-        status::Custom(
-            Status::InternalServerError,
-            Json(ErrorResponse {
-                error: "failed to create jwt token",
-            }),
-        )
-    })?;
+    if existing.is_some() {
+        return Err(email_taken());
+    }
+    let user = users
+        .create(&new_user.email, &new_user.username, &password)
+        .await
+        .map_err(|e| {
+            if is_unique_violation(&e) {
+                email_taken()
+            } else {
+                e.into()
+            }
+        })?;
+    metrics.sign_ups.inc();
+
+    let token = generate_jwt(&user.id.to_string(), &user.role)
+        .map_err(|_| AppError::Internal("failed to create jwt token"))?;
 
     cookies.add(Cookie::build(("auth_token", token)));
 
-    Ok(Json(user))
+    Ok(Either::Left(Json(user)))
 }
 
-#[post("/sign-in", data = "<user>")]
-pub async fn sign_in<'a>(
-    db_pool: &rocket::State<MySqlPool>,
+/// Creates the account of a pending sign-up from its emailed token and signs
+/// the new user in.
+#[post("/confirm", data = "<confirmation>")]
+pub async fn confirm_sign_up(
+    users: &rocket::State<UserRepo>,
+    metrics: &rocket::State<Metrics>,
     cookies: &CookieJar<'_>,
-    user: Json<UserCredential>,
-) -> Result<Json<User>, status::Custom<Json<ErrorResponse<'a>>>> {
-    let exists = sqlx::query_scalar!(
-        "SELECT EXISTS(SELECT 1 FROM users WHERE email = ?)",
-        user.email
-    )
-    .fetch_one(db_pool.inner())
-    .await
-    .map_err(|_| {
-        status::Custom(
-            Status::InternalServerError,
-            Json(ErrorResponse {
-                error: "Database Error.",
-            }),
+    confirmation: Json<SignUpConfirmation>,
+) -> Result<Json<User>, AppError> {
+    let user = users
+        .confirm_pending(
+            &token_hash(confirmation.token.trim()),
+            Utc::now() - CONFIRMATION_TTL,
         )
-    })?;
-    if exists == 0 {
-        return Err(status::Custom(
-            Status::Unauthorized,
-            Json(ErrorResponse {
-                error: "email or password are incrrocet",
-            }),
-        ));
-    }
-
-    let record = sqlx::query!("SELECT * FROM users WHERE email = ?", user.email)
-        .fetch_one(db_pool.inner())
         .await
-        .unwrap();
-    let password_varifcation = verify_password(&user.password, &record.password);
-    if !password_varifcation {
-        return Err(status::Custom(
-            Status::Unauthorized,
-            Json(ErrorResponse {
-                error: "email or password are incrrocet",
-            }),
-        ));
-    }
+        .map_err(|e| match e {
+            RepoError::NotFound => AppError::BadRequest(
+                "invalid_token",
+                "The confirmation token is invalid or has expired",
+            ),
+            e if is_unique_violation(&e) => email_taken(),
+            e => e.into(),
+        })?;
+    metrics.sign_ups.inc();
 
-    let user = User {
-        id: record.id as i64,
-        username: record.username.clone(),
-        email: record.email.clone(),
-        role: "user".to_string(),
+    let token = generate_jwt(&user.id.to_string(), &user.role)
+        .map_err(|_| AppError::Internal("failed to create jwt token"))?;
+
+    cookies.add(Cookie::build(("auth_token", token)));
+
+    Ok(Json(user))
+}
+
+fn accepted() -> status::Accepted<Json<SignUpAccepted>> {
+    status::Accepted(Json(SignUpAccepted {
+        message: "Check your inbox to finish signing up",
+    }))
+}
+
+fn is_unique_violation(error: &RepoError) -> bool {
+    matches!(error, RepoError::Database(sqlx::Error::Database(e)) if e.is_unique_violation())
+}
+
+#[post("/sign-in", data = "<user>")]
+pub async fn sign_in(
+    users: &rocket::State<UserRepo>,
+    metrics: &rocket::State<Metrics>,
+    cookies: &CookieJar<'_>,
+    user: Valid<UserCredential>,
+) -> Result<Json<User>, AppError> {
+    let account = users.find_by_email(&user.email).await?;
+    // An unknown email still costs a verification, timing does not tell it apart
+    let password_hash = account
+        .as_ref()
+        .map_or(DUMMY_HASH, |account| account.password.as_str());
+    let password_varifcation = verify_password(&user.password, password_hash);
+    let Some(account) = account.filter(|_| password_varifcation) else {
+        metrics.failed_logins.inc();
+        return Err(AppError::Unauthorized("email or password are incrrocet"));
     };
 
-    let token = generate_jwt(&user.id.to_string(), &user.role).map_err(|_| {
-        status::Custom(
-            Status::InternalServerError,
-            Json(ErrorResponse {
-                error: "failed to create jwt token",
-            }),
-        )
-    })?;
+    let user = account.user;
+
+    let token = generate_jwt(&user.id.to_string(), &user.role)
+        .map_err(|_| AppError::Internal("failed to create jwt token"))?;
 
     cookies.add(Cookie::build(("auth_token", token)));
+    metrics.sign_ins.inc();
 
     Ok(Json(user))
 }
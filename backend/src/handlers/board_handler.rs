@@ -0,0 +1,66 @@
+use rocket::serde::json::Json;
+
+use crate::{
+    guards::jwt_guard::JwtAuth,
+    handlers::{
+        project_handler::ensure_writable,
+        task_handler::{ensure_unblocked, fetch_task},
+    },
+    models::{
+        error::AppError,
+        project::{Board, BoardColumn},
+        tasks::{BoardMove, TaskStatus, Tasks},
+    },
+    repository::{ProjectRepo, TaskRepo},
+    utils::parse_user_id,
+};
+
+#[get("/<id>/board")]
+pub async fn get_board(
+    projects: &rocket::State<ProjectRepo>,
+    user: JwtAuth,
+    id: i64,
+) -> Result<Json<Board>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let tasks = projects
+        .tasks(id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, "Project not found or accesse denied."))?;
+
+    // Tasks come sorted by rank, so each column keeps its manual order
+    let columns = [TaskStatus::Todo, TaskStatus::InProgress, TaskStatus::Done]
+        .into_iter()
+        .map(|status| BoardColumn {
+            status,
+            tasks: tasks
+                .iter()
+                .filter(|task| task.status == status)
+                .cloned()
+                .collect(),
+        })
+        .collect();
+
+    Ok(Json(Board {
+        project_id: id,
+        columns,
+    }))
+}
+
+#[put("/<task_id>/board", data = "<target>")]
+pub async fn move_on_board(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    target: Json<BoardMove>,
+) -> Result<Json<Tasks>, AppError> {
+    let user_id = parse_user_id(user)?;
+
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+    if target.status == TaskStatus::Done && task.status != TaskStatus::Done {
+        ensure_unblocked(tasks.inner(), task_id).await?;
+    }
+
+    Ok(Json(tasks.move_on_board(&task, &target).await?))
+}
@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use rocket::{http::Status, response::status, serde::json::Json};
+
+use crate::{
+    guards::{jwt_guard::JwtAuth, valid::Valid},
+    handlers::project_handler::ensure_writable,
+    models::{
+        bulk::{BulkItemResult, BulkOperation, BulkRequest, BulkResult},
+        error::AppError,
+    },
+    repository::{ProjectRepo, TaskRepo},
+    utils::parse_user_id,
+};
+
+/// Upper bound on the tasks touched by a single request.
+const MAX_BULK_ITEMS: usize = 500;
+
+fn too_many_items() -> AppError {
+    AppError::BadRequest("too_many_tasks", "A bulk operation is limited to 500 tasks")
+}
+
+/// Applies one operation to a list of tasks or to every task matching a filter.
+/// Items run in order within a single transaction, which is only committed when
+/// every item succeeded; otherwise the response is a 409 listing each failure.
+#[post("/bulk", data = "<request>")]
+pub async fn bulk_update(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    user: JwtAuth,
+    request: Valid<BulkRequest>,
+) -> Result<status::Custom<Json<BulkResult>>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let request = request.into_inner();
+
+    let operation = request.operation;
+
+    if let BulkOperation::MoveProject { project_id } = operation {
+        ensure_writable(projects.inner(), project_id, user_id).await?;
+    }
+
+    let ids: Vec<i64> = match (request.ids, request.filter) {
+        (Some(ids), None) => {
+            if ids.len() > MAX_BULK_ITEMS {
+                return Err(too_many_items());
+            }
+            let mut seen = HashSet::new();
+            ids.into_iter().filter(|id| seen.insert(*id)).collect()
+        }
+        (None, Some(filter)) => {
+            let ids = tasks
+                .select(user_id, &filter, MAX_BULK_ITEMS as i64 + 1)
+                .await?;
+            if ids.len() > MAX_BULK_ITEMS {
+                return Err(too_many_items());
+            }
+            ids
+        }
+        _ => {
+            return Err(AppError::BadRequest(
+                "missing_selection",
+                "Provide either ids or a filter",
+            ))
+        }
+    };
+
+    let outcomes = tasks.bulk(user_id, &ids, &operation).await?;
+    let results: Vec<BulkItemResult> = ids
+        .into_iter()
+        .zip(outcomes)
+        .map(|(id, outcome)| match outcome {
+            Ok(()) => BulkItemResult {
+                id,
+                ok: true,
+                error: None,
+                code: None,
+            },
+            Err(e) => {
+                let e = AppError::from_repo(e, "Task not found or access denied");
+                BulkItemResult {
+                    id,
+                    ok: false,
+                    error: Some(e.message().to_string()),
+                    code: Some(e.code()),
+                }
+            }
+        })
+        .collect();
+
+    let committed = results.iter().all(|result| result.ok);
+    let status = if committed {
+        Status::Ok
+    } else {
+        Status::Conflict
+    };
+    Ok(status::Custom(
+        status,
+        Json(BulkResult { committed, results }),
+    ))
+}
@@ -0,0 +1,99 @@
+use rocket::{response::status::NoContent, serde::json::Json};
+
+use crate::{
+    guards::{jwt_guard::JwtAuth, valid::Valid},
+    handlers::{project_handler::ensure_writable, task_handler::fetch_task},
+    models::{
+        checklist::{ChecklistItem, ChecklistOrder, NewChecklistItem},
+        error::AppError,
+    },
+    repository::{ChecklistRepo, ProjectRepo, TaskRepo},
+    utils::parse_user_id,
+};
+
+const ITEM_NOT_FOUND: &str = "Checklist item not found";
+
+#[get("/<task_id>/checklist")]
+pub async fn get_checklist(
+    tasks: &rocket::State<TaskRepo>,
+    checklists: &rocket::State<ChecklistRepo>,
+    user: JwtAuth,
+    task_id: i64,
+) -> Result<Json<Vec<ChecklistItem>>, AppError> {
+    let user_id = parse_user_id(user)?;
+    fetch_task(tasks.inner(), task_id, user_id).await?;
+    Ok(Json(checklists.list(task_id).await?))
+}
+
+#[post("/<task_id>/checklist", data = "<item>")]
+pub async fn add_checklist_item(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    checklists: &rocket::State<ChecklistRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    item: Valid<NewChecklistItem>,
+) -> Result<Json<ChecklistItem>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    // New items go to the end of the list
+    Ok(Json(checklists.add(task_id, &item.text).await?))
+}
+
+#[put("/<task_id>/checklist/order", data = "<order>")]
+pub async fn reorder_checklist(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    checklists: &rocket::State<ChecklistRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    order: Json<ChecklistOrder>,
+) -> Result<Json<Vec<ChecklistItem>>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    Ok(Json(checklists.reorder(task_id, &order.ids).await?))
+}
+
+#[put("/<task_id>/checklist/<id>/toggle")]
+pub async fn toggle_checklist_item(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    checklists: &rocket::State<ChecklistRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    id: i64,
+) -> Result<Json<ChecklistItem>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    let item = checklists
+        .toggle(id, task_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, ITEM_NOT_FOUND))?;
+    Ok(Json(item))
+}
+
+#[delete("/<task_id>/checklist/<id>")]
+pub async fn delete_checklist_item(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    checklists: &rocket::State<ChecklistRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    id: i64,
+) -> Result<NoContent, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    checklists
+        .delete(id, task_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, ITEM_NOT_FOUND))?;
+    Ok(NoContent)
+}
@@ -0,0 +1,140 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use rocket::{response::status::NoContent, serde::json::Json};
+
+use crate::{
+    guards::{jwt_guard::JwtAuth, valid::Valid},
+    handlers::{project_handler::ensure_writable, task_handler::fetch_task},
+    models::{
+        comment::{Comment, NewComment, UpdatedComment, MAX_REPLY_DEPTH},
+        error::AppError,
+    },
+    repository::{CommentRepo, ProjectRepo, TaskRepo},
+    utils::parse_user_id,
+};
+
+const COMMENT_NOT_FOUND: &str = "Comment not found or access denied";
+
+/// Attaches replies to their parents, `comments` in chronological order. Deleted
+/// comments are only kept while they still have replies, so a thread never loses
+/// its structure. Replies nested past `MAX_REPLY_DEPTH` by older versions hang
+/// off their ancestor at that depth.
+fn build_thread(comments: Vec<Comment>) -> Vec<Comment> {
+    let index: HashMap<i64, usize> = comments
+        .iter()
+        .enumerate()
+        .map(|(i, comment)| (comment.id, i))
+        .collect();
+    let mut parent: Vec<Option<usize>> = vec![None; comments.len()];
+    let mut depth = vec![0; comments.len()];
+    // A reply always has a larger id than its parent
+    let mut by_id: Vec<usize> = (0..comments.len()).collect();
+    by_id.sort_by_key(|&i| comments[i].id);
+    for i in by_id {
+        let Some(mut p) = comments[i].parent_id.and_then(|id| index.get(&id).copied()) else {
+            continue;
+        };
+        while depth[p] >= MAX_REPLY_DEPTH {
+            p = parent[p].expect("nested comments have a parent");
+        }
+        parent[i] = Some(p);
+        depth[i] = depth[p] + 1;
+    }
+
+    // Deepest first, so every reply is complete by the time it joins its parent
+    let mut order: Vec<usize> = (0..comments.len()).collect();
+    order.sort_by_key(|&i| Reverse(depth[i]));
+    let mut slots: Vec<Option<Comment>> = comments.into_iter().map(Some).collect();
+    let mut thread = Vec::new();
+    for i in order {
+        let comment = slots[i].take().expect("each comment is placed once");
+        if comment.deleted && comment.replies.is_empty() {
+            continue;
+        }
+        match parent[i] {
+            Some(p) => slots[p]
+                .as_mut()
+                .expect("parents are placed after their replies")
+                .replies
+                .push(comment),
+            None => thread.push(comment),
+        }
+    }
+    thread
+}
+
+#[get("/<task_id>/comments")]
+pub async fn get_comments(
+    tasks: &rocket::State<TaskRepo>,
+    comments: &rocket::State<CommentRepo>,
+    user: JwtAuth,
+    task_id: i64,
+) -> Result<Json<Vec<Comment>>, AppError> {
+    let user_id = parse_user_id(user)?;
+    // Comments are visible to whoever owns the project the task belongs to
+    fetch_task(tasks.inner(), task_id, user_id).await?;
+
+    Ok(Json(build_thread(comments.list(task_id).await?)))
+}
+
+#[post("/<task_id>/comments", data = "<comment>")]
+pub async fn create_comment(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    comments: &rocket::State<CommentRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    comment: Valid<NewComment>,
+) -> Result<Json<Comment>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    let comment = comments
+        .create(task_id, user_id, &comment)
+        .await
+        .map_err(|e| AppError::from_repo(e, "Parent comment not found"))?;
+    Ok(Json(comment))
+}
+
+#[put("/<task_id>/comments/<id>", data = "<comment>")]
+pub async fn update_comment(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    comments: &rocket::State<CommentRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    id: i64,
+    comment: Valid<UpdatedComment>,
+) -> Result<Json<Comment>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    // Only the author can edit a comment
+    let comment = comments
+        .edit(id, task_id, user_id, &comment.body)
+        .await
+        .map_err(|e| AppError::from_repo(e, COMMENT_NOT_FOUND))?;
+    Ok(Json(comment))
+}
+
+#[delete("/<task_id>/comments/<id>")]
+pub async fn delete_comment(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    comments: &rocket::State<CommentRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    id: i64,
+) -> Result<NoContent, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    comments
+        .delete(id, task_id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, COMMENT_NOT_FOUND))?;
+    Ok(NoContent)
+}
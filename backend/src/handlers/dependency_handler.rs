@@ -0,0 +1,68 @@
+use rocket::{response::status::NoContent, serde::json::Json};
+
+use crate::{
+    guards::jwt_guard::JwtAuth,
+    handlers::{project_handler::ensure_writable, task_handler::fetch_task},
+    models::{
+        error::AppError,
+        tasks::{NewDependency, TaskDependencies},
+    },
+    repository::{DependencyRepo, ProjectRepo, TaskRepo},
+    utils::parse_user_id,
+};
+
+#[get("/<id>/dependencies")]
+pub async fn get_dependencies(
+    tasks: &rocket::State<TaskRepo>,
+    dependencies: &rocket::State<DependencyRepo>,
+    user: JwtAuth,
+    id: i64,
+) -> Result<Json<TaskDependencies>, AppError> {
+    let user_id = parse_user_id(user)?;
+    fetch_task(tasks.inner(), id, user_id).await?;
+    Ok(Json(dependencies.list(id).await?))
+}
+
+#[post("/<id>/dependencies", data = "<dependency>")]
+pub async fn add_dependency(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    dependencies: &rocket::State<DependencyRepo>,
+    user: JwtAuth,
+    id: i64,
+    dependency: Json<NewDependency>,
+) -> Result<Json<TaskDependencies>, AppError> {
+    let user_id = parse_user_id(user)?;
+    if dependency.depends_on == id {
+        return Err(AppError::BadRequest(
+            "self_dependency",
+            "A task cannot depend on itself",
+        ));
+    }
+    let task = fetch_task(tasks.inner(), id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+    fetch_task(tasks.inner(), dependency.depends_on, user_id).await?;
+
+    dependencies.add(id, dependency.depends_on).await?;
+    Ok(Json(dependencies.list(id).await?))
+}
+
+#[delete("/<id>/dependencies/<depends_on>")]
+pub async fn remove_dependency(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    dependencies: &rocket::State<DependencyRepo>,
+    user: JwtAuth,
+    id: i64,
+    depends_on: i64,
+) -> Result<NoContent, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    dependencies
+        .remove(id, depends_on)
+        .await
+        .map_err(|e| AppError::from_repo(e, "Dependency not found"))?;
+    Ok(NoContent)
+}
@@ -0,0 +1,15 @@
+use rocket::{http::Status, Request};
+
+use crate::{guards::valid::RejectedFields, models::error::AppError};
+
+/// Renders the errors Rocket raises itself, failing guards, unknown routes and
+/// unparsable bodies, in the same shape as the handlers' errors.
+#[catch(default)]
+pub fn default_catcher(status: Status, request: &Request) -> AppError {
+    // A body rejected by `Valid` left its field errors behind
+    let rejected = request.local_cache(|| RejectedFields(Vec::new()));
+    if status == Status::UnprocessableEntity && !rejected.0.is_empty() {
+        return AppError::Validation(rejected.0.clone());
+    }
+    AppError::Http(status)
+}
@@ -0,0 +1,36 @@
+use rocket::serde::json::Json;
+
+use crate::{
+    guards::jwt_guard::JwtAuth,
+    models::{
+        error::AppError,
+        pagination::Pagination,
+        tasks::{ListOptions, TaskPage, TaskSort},
+    },
+    repository::TaskRepo,
+    utils::{filter, parse_user_id},
+};
+
+/// Tasks of the caller across projects, narrowed down with a filter query such as
+/// `project:Infra label:bug priority>=high due<7d -status:done`.
+#[get("/?<q>&<archived>&<sort>&<desc>&<pagination..>")]
+pub async fn list_tasks(
+    tasks: &rocket::State<TaskRepo>,
+    user: JwtAuth,
+    q: Option<&str>,
+    archived: Option<bool>,
+    sort: Option<TaskSort>,
+    desc: Option<bool>,
+    pagination: Pagination,
+) -> Result<Json<TaskPage>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let filter = filter::parse(q.unwrap_or_default()).map_err(AppError::InvalidQuery)?;
+    let options = ListOptions {
+        project_id: None,
+        include_archived: archived.unwrap_or(false),
+        sort: sort.unwrap_or_default(),
+        descending: desc.unwrap_or(false),
+    };
+    let page = tasks.query(user_id, &filter, &options, &pagination).await?;
+    Ok(Json(page))
+}
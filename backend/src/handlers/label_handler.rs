@@ -0,0 +1,67 @@
+use rocket::{response::status::NoContent, serde::json::Json};
+
+use crate::{
+    guards::{jwt_guard::JwtAuth, valid::Valid},
+    handlers::{project_handler::ensure_writable, task_handler::fetch_task},
+    models::{error::AppError, tasks::NewLabel},
+    repository::{LabelRepo, ProjectRepo, TaskRepo},
+    utils::{parse_user_id, validation::Validator},
+};
+
+/// Label taken from the path, bodies are normalized by `Valid` with the same rules.
+fn normalize_label(label: &str) -> Result<String, AppError> {
+    let mut label = label.to_string();
+    let mut validator = Validator::default();
+    validator.label("label", &mut label);
+    validator.finish().map(|()| label)
+}
+
+#[get("/<task_id>/labels")]
+pub async fn get_labels(
+    tasks: &rocket::State<TaskRepo>,
+    labels: &rocket::State<LabelRepo>,
+    user: JwtAuth,
+    task_id: i64,
+) -> Result<Json<Vec<String>>, AppError> {
+    let user_id = parse_user_id(user)?;
+    fetch_task(tasks.inner(), task_id, user_id).await?;
+    Ok(Json(labels.list(task_id).await?))
+}
+
+#[post("/<task_id>/labels", data = "<label>")]
+pub async fn add_label(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    labels: &rocket::State<LabelRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    label: Valid<NewLabel>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let label = label.into_inner().label;
+
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+    Ok(Json(labels.add(&task, user_id, &label).await?))
+}
+
+#[delete("/<task_id>/labels/<label>")]
+pub async fn remove_label(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    labels: &rocket::State<LabelRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    label: &str,
+) -> Result<NoContent, AppError> {
+    let user_id = parse_user_id(user)?;
+    let label = normalize_label(label)?;
+
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+    labels
+        .remove(&task, user_id, &label)
+        .await
+        .map_err(|e| AppError::from_repo(e, "Label not found on this task"))?;
+    Ok(NoContent)
+}
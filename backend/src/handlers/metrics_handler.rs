@@ -0,0 +1,10 @@
+use rocket::http::ContentType;
+
+use crate::telemetry::metrics::Metrics;
+
+/// Prometheus scrape endpoint, served on `METRICS_PORT` instead when it is set.
+#[get("/metrics")]
+pub fn metrics(metrics: &rocket::State<Metrics>) -> (ContentType, String) {
+    let content_type = ContentType::new("text", "plain").with_params(("version", "0.0.4"));
+    (content_type, metrics.render())
+}
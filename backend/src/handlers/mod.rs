@@ -1,3 +1,19 @@
+pub mod activity_handler;
+pub mod attachment_handler;
 pub mod auth_handlers;
+pub mod board_handler;
+pub mod bulk_handler;
+pub mod checklist_handler;
+pub mod comment_handler;
+pub mod dependency_handler;
+pub mod error_handler;
+pub mod filter_handler;
+pub mod label_handler;
+pub mod metrics_handler;
 pub mod project_handler;
+pub mod report_handler;
+pub mod search_handler;
 pub mod task_handler;
+pub mod time_handler;
+pub mod trash_handler;
+pub mod view_handler;
@@ -1,113 +1,100 @@
-use crate::{
-    models::project::ProjectData,
-    utils::{parse_date, parse_user_id},
-};
-use rocket::{
-    http::Status,
-    response::status::{self, NoContent},
-    serde::json::Json,
-};
+use std::collections::HashMap;
+
+use rocket::{response::status::NoContent, serde::json::Json};
 
 use crate::{
-    db::DB,
-    guards::jwt_guard::JwtAuth,
+    guards::{jwt_guard::JwtAuth, valid::Valid},
     models::{
-        error::ErrorResponse,
-        project::{NewProject, Project},
-        tasks::Tasks,
+        error::AppError,
+        project::{
+            NewProject, Project, ProjectData, ProjectSchedule, ProjectSummary, StatusSummary,
+        },
+        tasks::{TaskStatus, Tasks},
     },
+    repository::ProjectRepo,
+    utils::{graph, parse_user_id},
 };
 
+const PROJECT_NOT_FOUND: &str = "Project not found or accesse denied.";
+
 #[post("/", data = "<project>")]
 pub async fn create_project(
-    db_pool: &rocket::State<DB>,
+    projects: &rocket::State<ProjectRepo>,
     user: JwtAuth,
-    project: Json<NewProject>,
-) -> Result<Json<Project>, status::Custom<Json<ErrorResponse>>> {
+    project: Valid<NewProject>,
+) -> Result<Json<Project>, AppError> {
     let user_id = parse_user_id(user)?;
 
-    let result = sqlx::query!(
-        "INSERT INTO projects (name,user_id) VALUES (?, ?)",
-        project.name,
-        user_id
-    )
-    .execute(db_pool.inner())
-    .await
-    .map_err(|_| {
-        status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "Database error",
-            }),
-        )
-    })?;
-
-    let project = Project {
-        id: result.last_insert_id() as i64,
-        user_id,
-        name: project.name.clone(),
-    };
+    let project = projects
+        .create(user_id, &project.name)
+        .await
+        .map_err(|e| AppError::from_repo(e, "Project not found"))?;
     Ok(Json(project))
 }
 
+/// Loads a project owned by `user_id`.
+pub(crate) async fn fetch_project(
+    projects: &ProjectRepo,
+    id: i64,
+    user_id: i64,
+) -> Result<Project, AppError> {
+    projects
+        .find(id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, PROJECT_NOT_FOUND))
+}
+
+fn project_archived_error() -> AppError {
+    AppError::Conflict("project_archived", "Project is archived and read-only")
+}
+
+/// Rejects changes to an archived project of `user_id` and its tasks.
+pub(crate) async fn ensure_writable(
+    projects: &ProjectRepo,
+    project_id: i64,
+    user_id: i64,
+) -> Result<(), AppError> {
+    let archived = projects
+        .archived(project_id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, "Project not found"))?;
+    check_writable(archived)
+}
+
+/// Rejects changes to a project already loaded, `archived` being its state.
+pub(crate) fn check_writable(archived: bool) -> Result<(), AppError> {
+    if archived {
+        return Err(project_archived_error());
+    }
+    Ok(())
+}
+
+/// Projects of the caller, the archived ones only with `?archived=true`.
+#[get("/?<archived>")]
+pub async fn get_projects(
+    projects: &rocket::State<ProjectRepo>,
+    user: JwtAuth,
+    archived: Option<bool>,
+) -> Result<Json<Vec<Project>>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let projects = projects
+        .list(user_id, archived.unwrap_or(false))
+        .await
+        .map_err(|e| AppError::from_repo(e, "Project not found"))?;
+    Ok(Json(projects))
+}
+
 #[get("/<id>/tasks")]
 pub async fn get_project_tasks(
-    db_pool: &rocket::State<DB>,
+    projects: &rocket::State<ProjectRepo>,
     user: JwtAuth,
     id: i64,
-) -> Result<Json<ProjectData>, status::Custom<Json<ErrorResponse>>> {
+) -> Result<Json<ProjectData>, AppError> {
     let user_id = parse_user_id(user)?;
-    sqlx::query!(
-        "SELECT id FROM projects WHERE id = ? AND user_id = ?",
-        id,
-        user_id
-    )
-    .fetch_one(db_pool.inner())
-    .await
-    .map_err(|e| match e {
-        sqlx::Error::RowNotFound => status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "Project not found or accesse denied.",
-            }),
-        ),
-        _ => status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "Database error",
-            }),
-        ),
-    })?;
-    let records = sqlx::query!(
-        "SELECT * FROM tasks WHERE project_id = ? AND user_id = ?",
-        id,
-        user_id
-    )
-    .fetch_all(db_pool.inner())
-    .await
-    .map_err(|_| {
-        status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "Database error",
-            }),
-        )
-    })?;
-
-    let tasks: Vec<Tasks> = records
-        .iter()
-        .map(|row| {
-            let created_at = parse_date(row.created_at).expect("fiald to parse time");
-            Tasks {
-                id: row.id as i64,
-                project_id: id,
-                title: row.title.clone(),
-                user_id: row.user_id as i64,
-                description: row.description.clone(),
-                created_at,
-            }
-        })
-        .collect();
+    let tasks = projects
+        .tasks(id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, PROJECT_NOT_FOUND))?;
     let project = ProjectData {
         items: tasks.len(),
         data: tasks,
@@ -115,136 +102,174 @@ pub async fn get_project_tasks(
     Ok(Json(project))
 }
 
-#[get("/<id>")]
-pub async fn get_project(
-    db_pool: &rocket::State<DB>,
+#[get("/<id>/schedule")]
+pub async fn get_project_schedule(
+    projects: &rocket::State<ProjectRepo>,
     user: JwtAuth,
     id: i64,
-) -> Result<Json<Project>, status::Custom<Json<ErrorResponse>>> {
+) -> Result<Json<ProjectSchedule>, AppError> {
     let user_id = parse_user_id(user)?;
+    let tasks = projects
+        .tasks(id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, PROJECT_NOT_FOUND))?;
 
-    let record = sqlx::query!(
-        "SELECT * FROM projects WHERE id = ? AND user_id = ?",
-        id,
-        user_id
-    )
-    .fetch_one(db_pool.inner())
-    .await
-    .map_err(|e| match e {
-        sqlx::Error::RowNotFound => status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "Project not found or accesse denied.",
-            }),
-        ),
-        _ => status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "Database error.",
-            }),
-        ),
+    // Only the edges between tasks of this project take part in the schedule
+    let edges = projects
+        .dependencies(id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, "Project not found"))?;
+    let nodes: Vec<_> = tasks.iter().map(|task| (task.id, task.due_date)).collect();
+
+    let schedule = graph::schedule(&nodes, &edges).ok_or_else(|| {
+        AppError::Conflict("dependency_cycle", "Task dependencies contain a cycle")
     })?;
-    let project = Project {
-        id: record.id as i64,
-        user_id,
-        name: record.name,
-    };
+    let mut by_id: HashMap<i64, Tasks> = tasks.into_iter().map(|task| (task.id, task)).collect();
+    let order = schedule
+        .order
+        .iter()
+        .filter_map(|id| by_id.remove(id))
+        .collect();
+
+    Ok(Json(ProjectSchedule {
+        order,
+        critical_path: schedule.critical_path,
+    }))
+}
+
+#[get("/<id>/summary")]
+pub async fn get_project_summary(
+    projects: &rocket::State<ProjectRepo>,
+    user: JwtAuth,
+    id: i64,
+) -> Result<Json<ProjectSummary>, AppError> {
+    let user_id = parse_user_id(user)?;
+
+    let project = projects
+        .find(id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, PROJECT_NOT_FOUND))?;
+    let totals = projects
+        .status_totals(id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, "Project not found"))?;
+
+    // Every status is listed, even the ones without tasks
+    let by_status: Vec<StatusSummary> =
+        [TaskStatus::Todo, TaskStatus::InProgress, TaskStatus::Done]
+            .into_iter()
+            .map(|status| {
+                let row = totals.iter().find(|row| row.status == status);
+                StatusSummary {
+                    status,
+                    tasks: row.map_or(0, |row| row.tasks),
+                    story_points: row.map_or(0, |row| row.story_points),
+                    estimate_hours: row.map_or(0.0, |row| row.estimate_hours),
+                }
+            })
+            .collect();
 
+    Ok(Json(ProjectSummary {
+        id,
+        name: project.name,
+        tasks: by_status.iter().map(|summary| summary.tasks).sum(),
+        story_points: by_status.iter().map(|summary| summary.story_points).sum(),
+        estimate_hours: by_status.iter().map(|summary| summary.estimate_hours).sum(),
+        by_status,
+    }))
+}
+
+#[get("/<id>")]
+pub async fn get_project(
+    projects: &rocket::State<ProjectRepo>,
+    user: JwtAuth,
+    id: i64,
+) -> Result<Json<Project>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let project = projects
+        .find(id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, PROJECT_NOT_FOUND))?;
     Ok(Json(project))
 }
 
+/// Archives a project, its tasks stay readable but can no longer be changed.
+#[put("/<id>/archive")]
+pub async fn archive_project(
+    projects: &rocket::State<ProjectRepo>,
+    user: JwtAuth,
+    id: i64,
+) -> Result<Json<Project>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let not_found = |e| AppError::from_repo(e, PROJECT_NOT_FOUND);
+    projects.find(id, user_id).await.map_err(not_found)?;
+
+    projects.set_archived(id, true).await.map_err(not_found)?;
+
+    Ok(Json(projects.find(id, user_id).await.map_err(not_found)?))
+}
+
+#[put("/<id>/unarchive")]
+pub async fn unarchive_project(
+    projects: &rocket::State<ProjectRepo>,
+    user: JwtAuth,
+    id: i64,
+) -> Result<Json<Project>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let not_found = |e| AppError::from_repo(e, PROJECT_NOT_FOUND);
+    let project = projects.find(id, user_id).await.map_err(not_found)?;
+
+    projects.set_archived(id, false).await.map_err(not_found)?;
+
+    Ok(Json(Project {
+        archived_at: None,
+        ..project
+    }))
+}
+
 #[put("/<id>", data = "<project>")]
 pub async fn update_project(
-    db_pool: &rocket::State<DB>,
+    projects: &rocket::State<ProjectRepo>,
     user: JwtAuth,
-    project: Json<NewProject>,
+    project: Valid<NewProject>,
     id: i64,
-) -> Result<Json<Project>, status::Custom<Json<ErrorResponse>>> {
+) -> Result<Json<Project>, AppError> {
     // Parse the user_id from the JWT token
     let user_id = parse_user_id(user)?;
 
     // Check if the project exists and belongs to the user
-    sqlx::query!(
-        "SELECT * FROM projects WHERE id = ? AND user_id = ?",
-        id,
-        user_id
-    )
-    .fetch_one(db_pool.inner())
-    .await
-    .map_err(|_| {
-        status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "Project not found or access denied",
-            }),
-        )
-    })?;
+    let existing = projects
+        .find(id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, PROJECT_NOT_FOUND))?;
+    check_writable(existing.archived_at.is_some())?;
 
     // Update the project details in the database
-    sqlx::query!(
-        "UPDATE projects SET name = ? WHERE id = ? ",
-        project.name,
-        id
-    )
-    .execute(db_pool.inner())
-    .await
-    .map_err(|e| {
-        println!("{:?}", e);
-        status::Custom(
-            Status::InternalServerError,
-            Json(ErrorResponse {
-                error: "Failed to update the project",
-            }),
-        )
-    })?;
+    projects
+        .rename(id, user_id, &project.name)
+        .await
+        .map_err(|e| AppError::from_repo(e, PROJECT_NOT_FOUND))?;
 
     let project = Project {
-        id,
-        user_id,
         name: project.name.clone(),
+        ..existing
     };
     Ok(Json(project))
 }
 
+/// Moves the project to the trash together with all of its tasks.
 #[delete("/<project_id>")]
 pub async fn delete_project(
-    db_pool: &rocket::State<DB>,
+    projects: &rocket::State<ProjectRepo>,
     user: JwtAuth,
     project_id: i64,
-) -> Result<NoContent, status::Custom<Json<ErrorResponse>>> {
+) -> Result<NoContent, AppError> {
     let user_id = parse_user_id(user)?;
 
-    // Execute the deletion query and check the result
-    let result = sqlx::query!(
-        "DELETE FROM projects WHERE id = ? AND user_id = ?",
-        project_id,
-        user_id
-    )
-    .execute(db_pool.inner())
-    .await;
-
-    match result {
-        Ok(query_result) => {
-            if query_result.rows_affected() == 0 {
-                // No task was deleted, meaning task was not found
-                Err(status::Custom(
-                    Status::NotFound,
-                    Json(ErrorResponse {
-                        error: "Project not found of acesses denied",
-                    }),
-                ))
-            } else {
-                Ok(NoContent) // Return 204 No Content for successful deletion
-            }
-        }
-        Err(e) => {
-            println!("{:?}", e);
-            Err(status::Custom(
-                Status::InternalServerError,
-                Json(ErrorResponse {
-                    error: "Database Error.",
-                }),
-            ))
-        } // Handle database error
-    }
+    projects
+        .trash(project_id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, "Project not found of acesses denied"))?;
+
+    Ok(NoContent) // Return 204 No Content for successful deletion
 }
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use rocket::{http::ContentType, serde::json::Json, Either};
+
+use crate::{
+    guards::jwt_guard::JwtAuth,
+    models::{
+        error::AppError,
+        time_entry::{ReportFormat, ReportGrouping, TimeReport, TimeReportRow},
+    },
+    repository::{TimeEntryRepo, TimeQuery},
+    utils::parse_user_id,
+};
+
+/// Databases store dates up to the year 9999.
+const LAST_YEAR: i32 = 9999;
+
+fn parse_day(day: &str) -> Result<NaiveDate, AppError> {
+    let day = NaiveDate::parse_from_str(day, "%Y-%m-%d").map_err(|_| {
+        AppError::BadRequest("invalid_date", "Dates must be formatted as YYYY-MM-DD")
+    })?;
+    if day.year() > LAST_YEAR {
+        return Err(AppError::BadRequest(
+            "invalid_date",
+            "Dates past the year 9999 are not supported",
+        ));
+    }
+    Ok(day)
+}
+
+fn csv_field(value: &str) -> String {
+    // Spreadsheets run cells starting with these as formulas
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn to_csv(report: &TimeReport) -> String {
+    let mut csv = String::from("key,label,seconds,hours\n");
+    for row in &report.rows {
+        csv.push_str(&format!(
+            "{},{},{},{:.2}\n",
+            csv_field(&row.key),
+            csv_field(&row.label),
+            row.seconds,
+            row.seconds as f64 / 3600.0
+        ));
+    }
+    csv
+}
+
+/// Time spent on the caller's projects, grouped by project, task, user or day.
+/// `from` and `to` are inclusive days, entries count on the day they started and
+/// running timers count up to now.
+#[allow(clippy::too_many_arguments)]
+#[get("/time?<project_id>&<task_id>&<user_id>&<from>&<to>&<group_by>&<format>")]
+pub async fn time_report(
+    time_entries: &rocket::State<TimeEntryRepo>,
+    user: JwtAuth,
+    project_id: Option<i64>,
+    task_id: Option<i64>,
+    user_id: Option<i64>,
+    from: Option<&str>,
+    to: Option<&str>,
+    group_by: Option<ReportGrouping>,
+    format: Option<ReportFormat>,
+) -> Result<Either<Json<TimeReport>, (ContentType, String)>, AppError> {
+    let owner_id = parse_user_id(user)?;
+    let group_by = group_by.unwrap_or_default();
+
+    let from = match from {
+        Some(day) => Some(parse_day(day)?.and_hms_opt(0, 0, 0).unwrap().and_utc()),
+        None => None,
+    };
+    let until = match to {
+        Some(day) => Some(
+            parse_day(day)?
+                .checked_add_signed(Duration::days(1))
+                .filter(|next_day| next_day.year() <= LAST_YEAR)
+                .ok_or(AppError::BadRequest(
+                    "invalid_date",
+                    "`to` is past the latest supported date",
+                ))?
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc(),
+        ),
+        None => None,
+    };
+
+    let query = TimeQuery {
+        project_id,
+        task_id,
+        user_id,
+        from,
+        until,
+    };
+    let records = time_entries.tracked(owner_id, &query).await?;
+
+    let now = Utc::now();
+    let mut groups: HashMap<String, (String, i64)> = HashMap::new();
+    for row in records {
+        let started_at = row.started_at;
+        let ended_at = row.ended_at.unwrap_or(now);
+        let (key, label) = match group_by {
+            ReportGrouping::Project => (row.project_id.to_string(), row.project_name),
+            ReportGrouping::Task => (row.task_id.to_string(), row.title),
+            ReportGrouping::User => (row.user_id.to_string(), row.username),
+            ReportGrouping::Day => {
+                let day = started_at.date_naive().to_string();
+                (day.clone(), day)
+            }
+        };
+        let seconds = (ended_at - started_at).num_seconds().max(0);
+        groups.entry(key).or_insert((label, 0)).1 += seconds;
+    }
+
+    let mut rows: Vec<TimeReportRow> = groups
+        .into_iter()
+        .map(|(key, (label, seconds))| TimeReportRow {
+            key,
+            label,
+            seconds,
+        })
+        .collect();
+    if group_by == ReportGrouping::Day {
+        rows.sort_by(|a, b| a.key.cmp(&b.key));
+    } else {
+        rows.sort_by(|a, b| b.seconds.cmp(&a.seconds).then(a.key.cmp(&b.key)));
+    }
+
+    let report = TimeReport {
+        group_by,
+        total_seconds: rows.iter().map(|row| row.seconds).sum(),
+        rows,
+    };
+    Ok(match format.unwrap_or_default() {
+        ReportFormat::Json => Either::Left(Json(report)),
+        ReportFormat::Csv => Either::Right((ContentType::CSV, to_csv(&report))),
+    })
+}
@@ -0,0 +1,72 @@
+use rocket::serde::json::Json;
+
+use crate::{
+    guards::jwt_guard::JwtAuth,
+    models::{
+        error::AppError,
+        pagination::Pagination,
+        search::{DocKind, SearchResults},
+        tasks::{TaskPriority, TaskStatus},
+    },
+    search::{SearchBackend, SearchError, SearchQuery, MAX_OFFSET},
+    utils::parse_user_id,
+};
+
+/// Full text search over the user's projects, tasks and comments, best matches
+/// first. Archived projects and their content only show up with `?archived=true`.
+#[allow(clippy::too_many_arguments)]
+#[get("/?<q>&<kind>&<project_id>&<status>&<priority>&<archived>&<pagination..>")]
+pub async fn search(
+    search: &rocket::State<SearchBackend>,
+    user: JwtAuth,
+    q: &str,
+    kind: Option<DocKind>,
+    project_id: Option<i64>,
+    status: Option<TaskStatus>,
+    priority: Option<TaskPriority>,
+    archived: Option<bool>,
+    pagination: Pagination,
+) -> Result<Json<SearchResults>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let text = q.trim();
+    if text.is_empty() {
+        return Err(AppError::BadRequest(
+            "empty_query",
+            "Search query cannot be empty",
+        ));
+    }
+    let offset = pagination.offset() as usize;
+    if offset > MAX_OFFSET {
+        return Err(AppError::BadRequest(
+            "page_too_deep",
+            "Search results past the first 10000 cannot be paged to, refine the query",
+        ));
+    }
+
+    let query = SearchQuery {
+        user_id,
+        text: text.to_string(),
+        kind,
+        project_id,
+        status,
+        priority,
+        include_archived: archived.unwrap_or(false),
+        offset,
+        limit: pagination.size as usize,
+    };
+    let hits = search.search(&query).await.map_err(|e| match e {
+        SearchError::InvalidQuery(_) => {
+            AppError::BadRequest("invalid_search_query", "Invalid search query")
+        }
+        SearchError::Backend(e) => {
+            tracing::error!(error = %e, "search backend error");
+            AppError::Internal("Search Error")
+        }
+    })?;
+
+    Ok(Json(SearchResults {
+        page: pagination.page,
+        size: pagination.size,
+        hits,
+    }))
+}
@@ -1,216 +1,345 @@
 use chrono::Utc;
-use rocket::{
-    http::Status,
-    response::status::{self, NoContent},
-    serde::json::Json,
-};
+use rocket::{response::status::NoContent, serde::json::Json};
 
 use crate::{
-    db::DB,
-    guards::jwt_guard::JwtAuth,
+    guards::{jwt_guard::JwtAuth, valid::Valid},
+    handlers::project_handler::{check_writable, ensure_writable, fetch_project},
     models::{
-        error::ErrorResponse,
-        tasks::{NewTask, Tasks, UpdatedTask},
+        activity::{diff, FieldChange},
+        checklist::ChecklistProgress,
+        error::AppError,
+        tasks::{MoveTask, NewTask, SubtaskData, SubtaskPolicy, TaskStatus, Tasks, UpdatedTask},
     },
-    utils::{parse_date, parse_user_id},
+    repository::{ProjectRepo, TaskRepo},
+    telemetry::metrics::Metrics,
+    utils::{parse_user_id, rrule::RecurrenceRule, to_primitive_date},
 };
 
+const TASK_NOT_FOUND: &str = "Task not found or access denied";
+
+/// Loads a single task owned by `user_id`.
+pub(crate) async fn fetch_task(tasks: &TaskRepo, id: i64, user_id: i64) -> Result<Tasks, AppError> {
+    tasks
+        .find(id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, TASK_NOT_FOUND))
+}
+
+/// Validates a recurrence rule and normalizes it, an empty rule means no recurrence.
+fn parse_recurrence(rule: &str) -> Result<Option<String>, AppError> {
+    if rule.trim().is_empty() {
+        return Ok(None);
+    }
+    rule.parse::<RecurrenceRule>()
+        .map(|rule| Some(rule.to_string()))
+        .map_err(|error| AppError::invalid("recurrence", "invalid_recurrence", error))
+}
+
+fn task_blocked() -> AppError {
+    AppError::Conflict("task_blocked", "Task is blocked by unfinished dependencies")
+}
+
+/// Refuses to finish a task while one of its dependencies is still open.
+pub(crate) async fn ensure_unblocked(tasks: &TaskRepo, task_id: i64) -> Result<(), AppError> {
+    let blockers = tasks.open_blockers(task_id).await?;
+    if blockers > 0 {
+        return Err(task_blocked());
+    }
+    Ok(())
+}
+
 #[get("/<id>")]
 pub async fn get_tasks(
-    db_pool: &rocket::State<DB>,
+    tasks: &rocket::State<TaskRepo>,
     user: JwtAuth,
     id: i64,
-) -> Result<Json<Tasks>, status::Custom<Json<ErrorResponse>>> {
+) -> Result<Json<Tasks>, AppError> {
     let user_id = parse_user_id(user)?;
-    let record = sqlx::query!(
-        "SELECT * FROM tasks WHERE id = ? AND user_id = ?",
-        id,
-        user_id
-    )
-    .fetch_one(db_pool.inner())
-    .await
-    .map_err(|_| {
-        status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "Task not found or access denied",
-            }),
-        )
-    })?;
-    let created_at = parse_date(record.created_at)?;
-    let task = Tasks {
-        id: record.id as i64,
-        project_id: record.project_id.expect("msg") as i64,
-        user_id,
-        title: record.title,
-        description: record.description,
-        created_at,
-    };
+    let task = fetch_task(tasks.inner(), id, user_id).await?;
     Ok(Json(task))
 }
 
+#[get("/<id>/children")]
+pub async fn get_task_children(
+    tasks: &rocket::State<TaskRepo>,
+    user: JwtAuth,
+    id: i64,
+) -> Result<Json<SubtaskData>, AppError> {
+    let user_id = parse_user_id(user)?;
+    fetch_task(tasks.inner(), id, user_id).await?;
+
+    let children = tasks.children(id, user_id).await?;
+    // Roll the completion state of the whole subtree up to the parent
+    let progress = tasks.progress(id).await?;
+
+    Ok(Json(SubtaskData {
+        items: children.len(),
+        progress,
+        data: children,
+    }))
+}
+
 #[post("/?<project_id>", data = "<task>")]
 pub async fn create_task(
-    db_pool: &rocket::State<DB>,
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    metrics: &rocket::State<Metrics>,
     user: JwtAuth,
     project_id: i64,
-    task: Json<NewTask>,
-) -> Result<Json<Tasks>, status::Custom<Json<ErrorResponse>>> {
+    task: Valid<NewTask>,
+) -> Result<Json<Tasks>, AppError> {
     let user_id = parse_user_id(user)?;
-    let result = sqlx::query!(
-        "INSERT INTO tasks (user_id , project_id, title,description) VALUES (?,?,?,?)",
-        user_id,
-        project_id,
-        task.title,
-        Some(task.description.clone()),
-    )
-    .execute(db_pool.inner())
-    .await
-    .map_err(|_| {
-        status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "row not found",
-            }),
-        )
-    })?;
+    let project = fetch_project(projects.inner(), project_id, user_id).await?;
+    check_writable(project.archived_at.is_some())?;
+
+    // A subtask always lives in the same project as its parent
+    if let Some(parent_id) = task.parent_id {
+        let parent = fetch_task(tasks.inner(), parent_id, user_id).await?;
+        if parent.project_id != project_id {
+            return Err(AppError::BadRequest(
+                "parent_in_other_project",
+                "Parent task belongs to another project",
+            ));
+        }
+    }
+
+    if let Some(due_date) = task.due_date {
+        to_primitive_date(due_date)?;
+    }
+    let recurrence = task
+        .recurrence
+        .as_deref()
+        .map(parse_recurrence)
+        .transpose()?
+        .flatten();
+
     let new_task = Tasks {
-        id: result.last_insert_id() as i64,
+        id: 0,
         user_id,
         project_id,
+        parent_id: task.parent_id,
         title: task.title.clone(),
         description: task.description.clone(),
+        status: task.status.unwrap_or_default(),
+        priority: task.priority.unwrap_or_default(),
+        due_date: task.due_date,
+        recurrence,
+        story_points: task.story_points,
+        estimate_hours: task.estimate_hours,
+        rank: String::new(),
+        checklist: ChecklistProgress::default(),
         created_at: Utc::now(),
     };
+    let new_task = tasks
+        .create(new_task)
+        .await
+        .map_err(|e| AppError::from_repo(e, TASK_NOT_FOUND))?;
+    metrics.tasks_created.inc();
+
     Ok(Json(new_task))
 }
+
 #[put("/<task_id>", data = "<task>")]
 pub async fn update_task(
-    db_pool: &rocket::State<DB>,
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
     user: JwtAuth,
     task_id: i64,
-    task: Json<UpdatedTask>,
-) -> Result<Json<Tasks>, status::Custom<Json<ErrorResponse>>> {
+    task: Valid<UpdatedTask>,
+) -> Result<Json<Tasks>, AppError> {
     let user_id = parse_user_id(user)?;
 
     // Fetch the existing task
-    let existing_task = sqlx::query!(
-        "SELECT id, user_id, project_id, title, description, created_at 
-         FROM tasks WHERE id = ? AND user_id = ?",
-        task_id,
-        user_id
-    )
-    .fetch_one(db_pool.inner())
-    .await
-    .map_err(|_| {
-        status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "Task not found or access denied",
-            }),
-        )
-    })?;
-
-    // Check if the new project_id exists if provided
+    let existing_task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), existing_task.project_id, user_id).await?;
+
+    // The target project has to exist, belong to the user and accept changes
     if let Some(project_id) = task.project_id {
-        let project_exists = sqlx::query!("SELECT id FROM projects WHERE id = ?", project_id)
-            .fetch_optional(db_pool.inner())
-            .await
-            .map_err(|_| {
-                status::Custom(
-                    Status::InternalServerError,
-                    Json(ErrorResponse {
-                        error: "Failed to check project existence",
-                    }),
-                )
-            })?;
-
-        if project_exists.is_none() {
-            return Err(status::Custom(
-                Status::NotFound,
-                Json(ErrorResponse {
-                    error: "Project not found",
-                }),
-            ));
-        }
+        let project = fetch_project(projects.inner(), project_id, user_id).await?;
+        check_writable(project.archived_at.is_some())?;
     }
 
     // Merge new values or keep the old ones
-    let updated_title = task.title.clone().unwrap_or(existing_task.title);
-    let updated_description = task.description.clone().or(existing_task.description);
-    let updated_project_id = task
-        .project_id
-        .map(|pid| pid as i64)
-        .or(existing_task.project_id.map(|pid| pid as i64));
-
-    // Update the task
-    sqlx::query!(
-        "UPDATE tasks SET title = ?, description = ?, project_id = ? WHERE id = ? AND user_id = ?",
-        updated_title,
-        updated_description,
-        updated_project_id,
-        task_id,
-        user_id
-    )
-    .execute(db_pool.inner())
-    .await
-    .map_err(|_| {
-        status::Custom(
-            Status::InternalServerError,
-            Json(ErrorResponse {
-                error: "Failed to update the task",
-            }),
-        )
-    })?;
+    let updated_title = task
+        .title
+        .clone()
+        .unwrap_or_else(|| existing_task.title.clone());
+    let updated_description = task
+        .description
+        .clone()
+        .unwrap_or_else(|| existing_task.description.clone());
+    let updated_project_id = task.project_id.unwrap_or(existing_task.project_id);
+    let updated_status = task.status.unwrap_or(existing_task.status);
+    let updated_priority = task.priority.unwrap_or(existing_task.priority);
+    let updated_due_date = task.due_date.unwrap_or(existing_task.due_date);
+    let updated_recurrence = match task.recurrence.as_deref() {
+        Some(rule) => parse_recurrence(rule)?,
+        None => existing_task.recurrence.clone(),
+    };
+    let updated_story_points = task.story_points.unwrap_or(existing_task.story_points);
+    let updated_estimate_hours = task.estimate_hours.unwrap_or(existing_task.estimate_hours);
+    if let Some(due_date) = updated_due_date {
+        to_primitive_date(due_date)?;
+    }
+
+    if updated_status == TaskStatus::Done && existing_task.status != TaskStatus::Done {
+        ensure_unblocked(tasks.inner(), task_id).await?;
+    }
+
+    // Moving to another project detaches the task from its parent and takes the subtree along
+    let project_changed = updated_project_id != existing_task.project_id;
+    let updated_parent_id = if project_changed {
+        None
+    } else {
+        existing_task.parent_id
+    };
+
+    let changes: Vec<FieldChange> = [
+        diff("title", Some(&existing_task.title), Some(&updated_title)),
+        diff(
+            "description",
+            existing_task.description.as_ref(),
+            updated_description.as_ref(),
+        ),
+        diff(
+            "project_id",
+            Some(&existing_task.project_id),
+            Some(&updated_project_id),
+        ),
+        diff(
+            "parent_id",
+            existing_task.parent_id.as_ref(),
+            updated_parent_id.as_ref(),
+        ),
+        diff(
+            "status",
+            Some(&existing_task.status.as_str()),
+            Some(&updated_status.as_str()),
+        ),
+        diff(
+            "priority",
+            Some(&existing_task.priority.as_str()),
+            Some(&updated_priority.as_str()),
+        ),
+        diff(
+            "due_date",
+            existing_task
+                .due_date
+                .map(|date| date.to_rfc3339())
+                .as_ref(),
+            updated_due_date.map(|date| date.to_rfc3339()).as_ref(),
+        ),
+        diff(
+            "recurrence",
+            existing_task.recurrence.as_ref(),
+            updated_recurrence.as_ref(),
+        ),
+        diff(
+            "story_points",
+            existing_task.story_points.as_ref(),
+            updated_story_points.as_ref(),
+        ),
+        diff(
+            "estimate_hours",
+            existing_task.estimate_hours.as_ref(),
+            updated_estimate_hours.as_ref(),
+        ),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
 
     let updated_task = Tasks {
-        id: existing_task.id as i64,
-        user_id: existing_task.user_id as i64,
-        project_id: updated_project_id.unwrap_or(existing_task.project_id.unwrap() as i64),
+        project_id: updated_project_id,
+        parent_id: updated_parent_id,
         title: updated_title,
         description: updated_description,
-        created_at: parse_date(existing_task.created_at)?,
+        status: updated_status,
+        priority: updated_priority,
+        due_date: updated_due_date,
+        recurrence: updated_recurrence,
+        story_points: updated_story_points,
+        estimate_hours: updated_estimate_hours,
+        ..existing_task.clone()
     };
+    let updated_task = tasks
+        .update(&existing_task, updated_task, &changes)
+        .await
+        .map_err(|e| AppError::from_repo(e, TASK_NOT_FOUND))?;
 
     Ok(Json(updated_task))
 }
 
-#[delete("/<task_id>")]
-pub async fn delete_task(
-    db_pool: &rocket::State<DB>,
+#[put("/<task_id>/move", data = "<target>")]
+pub async fn move_task(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
     user: JwtAuth,
     task_id: i64,
-) -> Result<NoContent, status::Custom<Json<ErrorResponse>>> {
+    target: Json<MoveTask>,
+) -> Result<Json<Tasks>, AppError> {
     let user_id = parse_user_id(user)?;
 
-    // Execute the deletion query and check the result
-    let result = sqlx::query!(
-        "DELETE FROM tasks WHERE id = ? AND user_id = ?",
-        task_id,
-        user_id
-    )
-    .execute(db_pool.inner())
-    .await;
-
-    match result {
-        Ok(query_result) => {
-            if query_result.rows_affected() == 0 {
-                // No task was deleted, meaning task was not found
-                Err(status::Custom(
-                    Status::NotFound,
-                    Json(ErrorResponse {
-                        error: "Taks not found or acesses denied",
-                    }),
-                ))
-            } else {
-                Ok(NoContent) // Return 204 No Content for successful deletion
-            }
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    // The whole subtree follows the new parent into its project
+    let project_id = match target.parent_id {
+        Some(parent_id) => {
+            let parent = fetch_task(tasks.inner(), parent_id, user_id).await?;
+            ensure_writable(projects.inner(), parent.project_id, user_id).await?;
+            parent.project_id
         }
-        Err(_) => Err(status::Custom(
-            Status::InternalServerError,
-            Json(ErrorResponse {
-                error: "Database Error",
-            }),
-        )), // Handle database error
+        None => task.project_id,
+    };
+
+    let changes: Vec<FieldChange> = [
+        diff(
+            "parent_id",
+            task.parent_id.as_ref(),
+            target.parent_id.as_ref(),
+        ),
+        diff("project_id", Some(&task.project_id), Some(&project_id)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    tasks
+        .reparent(&task, target.parent_id, project_id, &changes)
+        .await
+        .map_err(|e| AppError::from_repo(e, TASK_NOT_FOUND))?;
+
+    Ok(Json(Tasks {
+        parent_id: target.parent_id,
+        project_id,
+        ..task
+    }))
+}
+
+/// Moves the task to the trash, from where it can be restored until it is purged.
+#[delete("/<task_id>?<subtasks>")]
+pub async fn delete_task(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    subtasks: Option<SubtaskPolicy>,
+) -> Result<NoContent, AppError> {
+    let user_id = parse_user_id(user)?;
+
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+    let descendants = tasks.subtree(task_id).await?;
+
+    // Never drop or orphan subtasks implicitly, the caller has to pick a policy
+    if !descendants.is_empty() && subtasks.is_none() {
+        return Err(AppError::Conflict(
+            "has_subtasks",
+            "Task has subtasks, use ?subtasks=reparent or ?subtasks=delete",
+        ));
     }
+
+    tasks.trash(&task, subtasks).await?;
+
+    Ok(NoContent) // Return 204 No Content, the task waits in the trash
 }
@@ -0,0 +1,90 @@
+use rocket::{response::status::NoContent, serde::json::Json};
+
+use crate::{
+    guards::{jwt_guard::JwtAuth, valid::Valid},
+    handlers::{project_handler::ensure_writable, task_handler::fetch_task},
+    models::{
+        error::AppError,
+        time_entry::{NewTimeEntry, TimeEntry},
+    },
+    repository::{ProjectRepo, TaskRepo, TimeEntryRepo},
+    utils::parse_user_id,
+};
+
+#[get("/<task_id>/time-entries")]
+pub async fn get_time_entries(
+    tasks: &rocket::State<TaskRepo>,
+    time_entries: &rocket::State<TimeEntryRepo>,
+    user: JwtAuth,
+    task_id: i64,
+) -> Result<Json<Vec<TimeEntry>>, AppError> {
+    let user_id = parse_user_id(user)?;
+    fetch_task(tasks.inner(), task_id, user_id).await?;
+    Ok(Json(time_entries.list(task_id).await?))
+}
+
+#[post("/<task_id>/time-entries", data = "<entry>")]
+pub async fn create_time_entry(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    time_entries: &rocket::State<TimeEntryRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    entry: Valid<NewTimeEntry>,
+) -> Result<Json<TimeEntry>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    Ok(Json(time_entries.create(task_id, user_id, &entry).await?))
+}
+
+#[delete("/<task_id>/time-entries/<id>")]
+pub async fn delete_time_entry(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    time_entries: &rocket::State<TimeEntryRepo>,
+    user: JwtAuth,
+    task_id: i64,
+    id: i64,
+) -> Result<NoContent, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    time_entries
+        .delete(id, task_id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, "Time entry not found or access denied"))?;
+    Ok(NoContent)
+}
+
+#[post("/<task_id>/timer/start")]
+pub async fn start_timer(
+    tasks: &rocket::State<TaskRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    time_entries: &rocket::State<TimeEntryRepo>,
+    user: JwtAuth,
+    task_id: i64,
+) -> Result<Json<TimeEntry>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let task = fetch_task(tasks.inner(), task_id, user_id).await?;
+    ensure_writable(projects.inner(), task.project_id, user_id).await?;
+
+    Ok(Json(time_entries.start(task_id, user_id).await?))
+}
+
+#[post("/<task_id>/timer/stop")]
+pub async fn stop_timer(
+    time_entries: &rocket::State<TimeEntryRepo>,
+    user: JwtAuth,
+    task_id: i64,
+) -> Result<Json<TimeEntry>, AppError> {
+    let user_id = parse_user_id(user)?;
+
+    let entry = time_entries
+        .stop(task_id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, "No timer running on this task"))?;
+    Ok(Json(entry))
+}
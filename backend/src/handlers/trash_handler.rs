@@ -0,0 +1,54 @@
+use rocket::serde::json::Json;
+
+use crate::{
+    guards::jwt_guard::JwtAuth,
+    handlers::task_handler::fetch_task,
+    models::{error::AppError, project::Project, tasks::Tasks, trash::Trash},
+    repository::{TaskRepo, TrashRepo},
+    utils::parse_user_id,
+};
+
+const NOT_IN_TRASH: &str = "Not found in the trash";
+
+#[get("/")]
+pub async fn get_trash(
+    trash: &rocket::State<TrashRepo>,
+    user: JwtAuth,
+) -> Result<Json<Trash>, AppError> {
+    let user_id = parse_user_id(user)?;
+    Ok(Json(trash.list(user_id).await?))
+}
+
+/// Restores a task and the subtasks deleted along with it. A parent that is
+/// still in the trash is not restored, the task becomes a root task instead.
+#[post("/tasks/<task_id>/restore")]
+pub async fn restore_task(
+    tasks: &rocket::State<TaskRepo>,
+    trash: &rocket::State<TrashRepo>,
+    user: JwtAuth,
+    task_id: i64,
+) -> Result<Json<Tasks>, AppError> {
+    let user_id = parse_user_id(user)?;
+
+    trash
+        .restore_task(task_id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, NOT_IN_TRASH))?;
+    Ok(Json(fetch_task(tasks.inner(), task_id, user_id).await?))
+}
+
+/// Restores a project with the tasks that were deleted along with it.
+#[post("/projects/<project_id>/restore")]
+pub async fn restore_project(
+    trash: &rocket::State<TrashRepo>,
+    user: JwtAuth,
+    project_id: i64,
+) -> Result<Json<Project>, AppError> {
+    let user_id = parse_user_id(user)?;
+
+    let project = trash
+        .restore_project(project_id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, NOT_IN_TRASH))?;
+    Ok(Json(project))
+}
@@ -0,0 +1,134 @@
+use rocket::{response::status::NoContent, serde::json::Json};
+
+use crate::{
+    guards::{jwt_guard::JwtAuth, valid::Valid},
+    handlers::project_handler::fetch_project,
+    models::{
+        error::AppError,
+        pagination::Pagination,
+        tasks::{ListOptions, TaskPage},
+        view::{NewSavedView, SavedView},
+    },
+    repository::{ProjectRepo, TaskRepo, ViewRepo},
+    utils::{filter, parse_user_id},
+};
+
+fn not_view_owner() -> AppError {
+    AppError::Forbidden("Only the owner can change a saved view")
+}
+
+/// Loads a view owned by `user_id` or shared in one of their projects.
+async fn fetch_view(views: &ViewRepo, id: i64, user_id: i64) -> Result<SavedView, AppError> {
+    views
+        .find(id, user_id)
+        .await
+        .map_err(|e| AppError::from_repo(e, "Saved view not found or access denied"))
+}
+
+/// Checks the query with the parser the listing uses, `Valid` already trimmed it.
+fn check_query(view: &NewSavedView) -> Result<(), AppError> {
+    let query = view.query.as_deref().unwrap_or_default();
+    filter::parse(query).map_err(AppError::InvalidQuery)?;
+    Ok(())
+}
+
+/// Views of the caller and the ones shared in their projects, only those of one
+/// project with `?project_id=`.
+#[get("/?<project_id>")]
+pub async fn get_views(
+    views: &rocket::State<ViewRepo>,
+    user: JwtAuth,
+    project_id: Option<i64>,
+) -> Result<Json<Vec<SavedView>>, AppError> {
+    let user_id = parse_user_id(user)?;
+    Ok(Json(views.list(user_id, project_id).await?))
+}
+
+#[get("/<id>")]
+pub async fn get_view(
+    views: &rocket::State<ViewRepo>,
+    user: JwtAuth,
+    id: i64,
+) -> Result<Json<SavedView>, AppError> {
+    let user_id = parse_user_id(user)?;
+    Ok(Json(fetch_view(views.inner(), id, user_id).await?))
+}
+
+/// Saves a view, the query is checked with the same parser the listing uses.
+#[post("/", data = "<view>")]
+pub async fn create_view(
+    views: &rocket::State<ViewRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    user: JwtAuth,
+    view: Valid<NewSavedView>,
+) -> Result<Json<SavedView>, AppError> {
+    let user_id = parse_user_id(user)?;
+    check_query(&view)?;
+    if let Some(project_id) = view.project_id {
+        fetch_project(projects.inner(), project_id, user_id).await?;
+    }
+
+    Ok(Json(views.create(user_id, &view).await?))
+}
+
+/// Replaces a view, shared views can only be changed by their owner.
+#[put("/<id>", data = "<view>")]
+pub async fn update_view(
+    views: &rocket::State<ViewRepo>,
+    projects: &rocket::State<ProjectRepo>,
+    user: JwtAuth,
+    id: i64,
+    view: Valid<NewSavedView>,
+) -> Result<Json<SavedView>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let existing = fetch_view(views.inner(), id, user_id).await?;
+    if existing.user_id != user_id {
+        return Err(not_view_owner());
+    }
+    check_query(&view)?;
+    if let Some(project_id) = view.project_id {
+        fetch_project(projects.inner(), project_id, user_id).await?;
+    }
+
+    Ok(Json(views.update(id, user_id, &view).await?))
+}
+
+#[delete("/<id>")]
+pub async fn delete_view(
+    views: &rocket::State<ViewRepo>,
+    user: JwtAuth,
+    id: i64,
+) -> Result<NoContent, AppError> {
+    let user_id = parse_user_id(user)?;
+    let view = fetch_view(views.inner(), id, user_id).await?;
+    if view.user_id != user_id {
+        return Err(not_view_owner());
+    }
+
+    views.delete(id).await?;
+    Ok(NoContent)
+}
+
+/// Runs the view's query with its sort order. A view shared in a project only
+/// lists that project's tasks, even once the project is archived.
+#[get("/<id>/tasks?<pagination..>")]
+pub async fn run_view(
+    views: &rocket::State<ViewRepo>,
+    tasks: &rocket::State<TaskRepo>,
+    user: JwtAuth,
+    id: i64,
+    pagination: Pagination,
+) -> Result<Json<TaskPage>, AppError> {
+    let user_id = parse_user_id(user)?;
+    let view = fetch_view(views.inner(), id, user_id).await?;
+    let filter = filter::parse(&view.query).map_err(AppError::InvalidQuery)?;
+
+    let options = ListOptions {
+        project_id: view.project_id,
+        include_archived: view.include_archived || view.project_id.is_some(),
+        sort: view.sort,
+        descending: view.descending,
+    };
+    let page = tasks.query(user_id, &filter, &options, &pagination).await?;
+    Ok(Json(page))
+}
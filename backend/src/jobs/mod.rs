@@ -0,0 +1,3 @@
+pub mod purge;
+pub mod recurrence;
+pub mod search_index;
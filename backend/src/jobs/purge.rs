@@ -0,0 +1,68 @@
+use std::{env, time::Duration};
+
+use chrono::Utc;
+use rocket::{fairing::AdHoc, tokio};
+use tracing::Instrument;
+
+use crate::repository::{AttachmentRepo, RepoError, TrashRepo};
+
+/// Permanently deletes projects and tasks that have been in the trash for more
+/// than `retention_days`, along with attachment content nothing refers to anymore.
+async fn purge_trash(
+    trash: &TrashRepo,
+    attachments: &AttachmentRepo,
+    retention_days: i64,
+) -> Result<(), RepoError> {
+    let cutoff = Utc::now() - chrono::Duration::days(retention_days);
+    let blobs = trash.purge(cutoff).await?;
+
+    for sha256 in blobs {
+        match attachments.release(&sha256).await {
+            Err(RepoError::Storage(e)) => {
+                tracing::warn!(error = ?e, sha256, "failed to delete an attachment blob")
+            }
+            result => result?,
+        }
+    }
+    Ok(())
+}
+
+/// Background job emptying the trash. Items are kept for `TRASH_RETENTION_DAYS`
+/// days (default 30) and the job runs every `PURGE_INTERVAL_SECS` seconds (default 3600).
+pub fn purge_job() -> AdHoc {
+    AdHoc::on_liftoff("Trash purge", |rocket| {
+        Box::pin(async move {
+            let trash = rocket
+                .state::<TrashRepo>()
+                .expect("trash repository")
+                .clone();
+            let attachments = rocket
+                .state::<AttachmentRepo>()
+                .expect("attachment repository")
+                .clone();
+            let retention_days: i64 = env::var("TRASH_RETENTION_DAYS")
+                .ok()
+                .and_then(|days| days.parse().ok())
+                .unwrap_or(30);
+            let period = env::var("PURGE_INTERVAL_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .unwrap_or(3600)
+                // A zero period would make the interval panic
+                .max(1);
+
+            tokio::spawn(
+                async move {
+                    let mut interval = tokio::time::interval(Duration::from_secs(period));
+                    loop {
+                        interval.tick().await;
+                        if let Err(e) = purge_trash(&trash, &attachments, retention_days).await {
+                            tracing::error!(error = ?e, "trash purge failed");
+                        }
+                    }
+                }
+                .instrument(tracing::info_span!("job", name = "trash_purge")),
+            );
+        })
+    })
+}
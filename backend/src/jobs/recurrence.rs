@@ -0,0 +1,36 @@
+use std::{env, time::Duration};
+
+use rocket::{fairing::AdHoc, tokio};
+use tracing::Instrument;
+
+use crate::repository::TaskRepo;
+
+/// Background job creating the next occurrence of recurring tasks whose current
+/// occurrence is past due, so a missed chore does not end its series.
+/// Runs every `RECURRENCE_INTERVAL_SECS` seconds (default 300).
+pub fn recurrence_job() -> AdHoc {
+    AdHoc::on_liftoff("Recurring tasks", |rocket| {
+        Box::pin(async move {
+            let tasks = rocket.state::<TaskRepo>().expect("task repository").clone();
+            let period = env::var("RECURRENCE_INTERVAL_SECS")
+                .ok()
+                .and_then(|secs| secs.parse().ok())
+                .unwrap_or(300)
+                // A zero period would make the interval panic
+                .max(1);
+
+            tokio::spawn(
+                async move {
+                    let mut interval = tokio::time::interval(Duration::from_secs(period));
+                    loop {
+                        interval.tick().await;
+                        if let Err(e) = tasks.roll_overdue().await {
+                            tracing::error!(error = ?e, "rolling over recurring tasks failed");
+                        }
+                    }
+                }
+                .instrument(tracing::info_span!("job", name = "recurrence")),
+            );
+        })
+    })
+}
@@ -0,0 +1,41 @@
+use rocket::{fairing::AdHoc, tokio};
+use tracing::Instrument;
+
+use crate::{
+    repository::SearchRepo,
+    search::{SearchBackend, SearchError},
+};
+
+async fn rebuild_index(documents: &SearchRepo, search: &SearchBackend) -> Result<(), SearchError> {
+    let docs = documents.documents().await?;
+    search.rebuild(docs).await
+}
+
+/// Rebuilds an index kept outside the database from scratch on start, so changes
+/// missed while the server was down or a sync failed do not linger.
+pub fn search_index_job() -> AdHoc {
+    AdHoc::on_liftoff("Search index", |rocket| {
+        Box::pin(async move {
+            let documents = rocket
+                .state::<SearchRepo>()
+                .expect("search repository")
+                .clone();
+            let search = rocket
+                .state::<SearchBackend>()
+                .expect("search backend")
+                .clone();
+            if !search.needs_sync() {
+                return;
+            }
+
+            tokio::spawn(
+                async move {
+                    if let Err(e) = rebuild_index(&documents, &search).await {
+                        tracing::error!(error = ?e, "search index rebuild failed");
+                    }
+                }
+                .instrument(tracing::info_span!("job", name = "search_index")),
+            );
+        })
+    })
+}
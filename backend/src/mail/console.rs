@@ -0,0 +1,17 @@
+use super::{Email, MailError, Mailer};
+
+/// Logs emails instead of sending them.
+pub struct ConsoleMailer;
+
+#[rocket::async_trait]
+impl Mailer for ConsoleMailer {
+    async fn send(&self, email: Email) -> Result<(), MailError> {
+        tracing::info!(
+            to = %email.to,
+            subject = %email.subject,
+            body = %email.body,
+            "email not sent, no SMTP server configured"
+        );
+        Ok(())
+    }
+}
@@ -1,32 +1,165 @@
 #[macro_use]
 extern crate rocket;
-use db::{db_connection, DB};
+use std::env;
+
+use auth::signup::SignupMode;
+use db::{db_connection, Database, SqliteDB, DB};
 use dotenv::dotenv;
-use rocket::{Build, Rocket};
+use handlers::error_handler::default_catcher;
+use jobs::{purge::purge_job, recurrence::recurrence_job, search_index::search_index_job};
+use mail::mailer_from_env;
+use repository::Repositories;
+use rocket::{fairing::AdHoc, Build, Rocket};
 use routes::{
-    auth_routes,
-    project_routes::{self, project_routes},
-    tasks_routes,
+    attachment_routes, auth_routes, checklist_routes, comment_routes, dependency_routes,
+    label_routes, metrics_routes, project_routes, report_routes, search_routes, tasks_routes,
+    time_routes, trash_routes, view_routes,
+};
+use search::{search_from_env, SearchBackend};
+use storage::{storage_from_env, AttachmentLimits, FileStorage};
+use telemetry::{
+    init_tracing,
+    metrics::{Metrics, RequestMetrics},
+    request::{traced, RequestTracing},
 };
 mod auth;
 mod db;
 mod guards;
 mod handlers;
+mod jobs;
+mod mail;
 mod models;
+mod repository;
 mod routes;
+mod search;
+mod storage;
+mod telemetry;
 mod utils;
+#[allow(dead_code)]
 #[get("/")]
 fn index() -> &'static str {
     "Hello, world!"
 }
 
+/// Serves `/metrics` next to the API, or on its own server when `METRICS_PORT` is set
+/// so it can stay off the public network.
+fn with_metrics(rocket: Rocket<Build>, metrics: Metrics) -> Rocket<Build> {
+    let rocket = rocket.manage(metrics.clone()).attach(RequestMetrics);
+    let Some(port) = env::var("METRICS_PORT")
+        .ok()
+        .and_then(|port| port.parse::<u16>().ok())
+    else {
+        return rocket.mount("/", metrics_routes::metrics_routes());
+    };
+    rocket.attach(AdHoc::on_liftoff("Metrics server", move |_| {
+        Box::pin(async move {
+            let server = rocket::custom(rocket::Config::figment().merge(("port", port)))
+                .manage(metrics)
+                .mount("/", metrics_routes::metrics_routes());
+            rocket::tokio::spawn(async move {
+                if let Err(e) = server.launch().await {
+                    tracing::error!(error = %e, "metrics server failed");
+                }
+            });
+        })
+    }))
+}
+
+/// Manages the repositories, starts the background jobs and mounts every route.
+fn with_repositories(
+    rocket: Rocket<Build>,
+    repositories: Repositories,
+    search: SearchBackend,
+    storage: FileStorage,
+    metrics: Metrics,
+) -> Rocket<Build> {
+    with_metrics(rocket, metrics)
+        .attach(RequestTracing)
+        .register("/", catchers![default_catcher])
+        .manage(repositories.users)
+        .manage(repositories.projects)
+        .manage(repositories.tasks)
+        .manage(repositories.comments)
+        .manage(repositories.attachments)
+        .manage(repositories.checklists)
+        .manage(repositories.time_entries)
+        .manage(repositories.labels)
+        .manage(repositories.dependencies)
+        .manage(repositories.activity)
+        .manage(repositories.trash)
+        .manage(repositories.views)
+        .manage(repositories.search)
+        .manage(search)
+        .manage(storage)
+        .manage(AttachmentLimits::from_env())
+        .manage(SignupMode::from_env())
+        .manage(mailer_from_env())
+        .attach(recurrence_job())
+        .attach(purge_job())
+        .attach(search_index_job())
+        .mount("/auth", traced(auth_routes::auth_routes()))
+        .mount("/task", traced(tasks_routes::tasks_routes()))
+        .mount("/task", traced(dependency_routes::dependency_routes()))
+        .mount("/task", traced(comment_routes::comment_routes()))
+        .mount("/task", traced(attachment_routes::attachment_routes()))
+        .mount("/task", traced(checklist_routes::checklist_routes()))
+        .mount("/task", traced(time_routes::time_routes()))
+        .mount("/task", traced(label_routes::label_routes()))
+        .mount("/project", traced(project_routes::project_routes()))
+        .mount("/reports", traced(report_routes::report_routes()))
+        .mount("/trash", traced(trash_routes::trash_routes()))
+        .mount("/search", traced(search_routes::search_routes()))
+        .mount("/views", traced(view_routes::view_routes()))
+}
+
+fn mysql_rocket(db_pool: DB) -> Rocket<Build> {
+    let search = search_from_env(Some(&db_pool));
+    let storage = storage_from_env();
+    let repositories = Repositories::mysql(&db_pool, &search, &storage);
+    with_repositories(
+        rocket::build(),
+        repositories,
+        search,
+        storage,
+        Metrics::new(db_pool),
+    )
+}
+
+fn sqlite_rocket(db_pool: SqliteDB) -> Rocket<Build> {
+    let search = search_from_env(None);
+    let storage = storage_from_env();
+    let repositories = Repositories::sqlite(&db_pool, &search, &storage);
+    with_repositories(
+        rocket::build(),
+        repositories,
+        search,
+        storage,
+        Metrics::new(db_pool),
+    )
+}
+
+#[cfg(feature = "postgres")]
+fn postgres_rocket(db_pool: db::PgDB) -> Rocket<Build> {
+    let search = search_from_env(None);
+    let storage = storage_from_env();
+    let repositories = Repositories::postgres(&db_pool, &search, &storage);
+    with_repositories(
+        rocket::build(),
+        repositories,
+        search,
+        storage,
+        Metrics::new(db_pool),
+    )
+}
+
 #[launch]
 async fn rocket() -> Rocket<Build> {
     dotenv().ok();
-    let db_pool: DB = db_connection().await;
-    rocket::build()
-        .manage(db_pool)
-        .mount("/auth", auth_routes::auth_routes())
-        .mount("/task", tasks_routes::tasks_routes())
-        .mount("/project", project_routes::project_routes())
+    init_tracing();
+    match db_connection().await {
+        Database::MySql(db_pool) => mysql_rocket(db_pool),
+        Database::Sqlite(db_pool) => sqlite_rocket(db_pool),
+        #[cfg(feature = "postgres")]
+        Database::Postgres(db_pool) => postgres_rocket(db_pool),
+    }
 }
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityAction {
+    Created,
+    Updated,
+    Deleted,
+    Restored,
+}
+
+impl ActivityAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityAction::Created => "created",
+            ActivityAction::Updated => "updated",
+            ActivityAction::Deleted => "deleted",
+            ActivityAction::Restored => "restored",
+        }
+    }
+}
+
+/// One entry of the activity log. Updates carry the changed field with its old
+/// and new value rendered as text, the other actions carry no field.
+#[derive(Serialize)]
+pub struct Activity {
+    pub id: i64,
+    pub task_id: i64,
+    pub project_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub action: String,
+    pub field: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A changed field of a task, values rendered as text.
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// The change of `field` from `old` to `new`, `None` when the value is the same.
+pub fn diff<T: PartialEq + ToString>(
+    field: &'static str,
+    old: Option<&T>,
+    new: Option<&T>,
+) -> Option<FieldChange> {
+    (old != new).then(|| FieldChange {
+        field,
+        old: old.map(T::to_string),
+        new: new.map(T::to_string),
+    })
+}
+
+#[derive(Serialize)]
+pub struct ActivityPage {
+    pub page: i64,
+    pub size: i64,
+    pub total: i64,
+    pub data: Vec<Activity>,
+}
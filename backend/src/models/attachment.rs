@@ -0,0 +1,66 @@
+use std::pin::Pin;
+
+use chrono::{DateTime, Utc};
+use rocket::{
+    http::ContentType,
+    response::{self, Responder},
+    tokio::io::AsyncRead,
+    Request, Response,
+};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct Attachment {
+    pub id: i64,
+    pub task_id: i64,
+    pub user_id: i64,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Streams an attachment from the storage backend to the client.
+pub struct AttachmentDownload {
+    pub reader: Pin<Box<dyn AsyncRead + Send>>,
+    pub content_type: ContentType,
+    pub filename: String,
+}
+
+/// `Content-Disposition` of a download: an ASCII `filename` for old clients and
+/// the exact name as UTF-8 in `filename*` (RFC 6266).
+fn content_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut encoded = String::with_capacity(filename.len());
+    for byte in filename.bytes() {
+        if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback, encoded
+    )
+}
+
+impl<'r> Responder<'r, 'static> for AttachmentDownload {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        Response::build()
+            .header(self.content_type)
+            .raw_header("Content-Disposition", content_disposition(&self.filename))
+            .streamed_body(self.reader)
+            .ok()
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use super::tasks::{TaskPriority, TaskStatus};
+use crate::utils::validation::{Validate, Validator};
+
+/// The change applied to every selected task.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkOperation {
+    MoveProject { project_id: i64 },
+    SetStatus { status: TaskStatus },
+    SetPriority { priority: TaskPriority },
+    AddLabel { label: String },
+    Delete,
+}
+
+/// Selects tasks by their attributes, every given criterion has to match.
+#[derive(Deserialize, Default)]
+pub struct TaskFilter {
+    pub project_id: Option<i64>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    pub label: Option<String>,
+}
+
+/// Body of `POST /task/bulk`, with either `ids` or `filter`.
+#[derive(Deserialize)]
+pub struct BulkRequest {
+    pub ids: Option<Vec<i64>>,
+    pub filter: Option<TaskFilter>,
+    pub operation: BulkOperation,
+}
+
+impl Validate for BulkRequest {
+    fn validate(&mut self, v: &mut Validator) {
+        if let BulkOperation::AddLabel { label } = &mut self.operation {
+            v.label("operation.label", label);
+        }
+        if let Some(label) = self
+            .filter
+            .as_mut()
+            .and_then(|filter| filter.label.as_mut())
+        {
+            v.label("filter.label", label);
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct BulkItemResult {
+    pub id: i64,
+    pub ok: bool,
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static str>,
+}
+
+/// Outcome of a bulk operation. Nothing is committed unless every item succeeded.
+#[derive(Serialize)]
+pub struct BulkResult {
+    pub committed: bool,
+    pub results: Vec<BulkItemResult>,
+}
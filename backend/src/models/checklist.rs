@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::validation::{Validate, Validator, MAX_VARCHAR};
+
+#[derive(Serialize)]
+pub struct ChecklistItem {
+    pub id: i64,
+    pub task_id: i64,
+    pub text: String,
+    pub checked: bool,
+    pub position: i64,
+}
+
+#[derive(Deserialize)]
+pub struct NewChecklistItem {
+    pub text: String,
+}
+
+impl Validate for NewChecklistItem {
+    fn validate(&mut self, v: &mut Validator) {
+        v.text("text", &mut self.text, MAX_VARCHAR);
+    }
+}
+
+/// Every item id of the checklist, in the new order.
+#[derive(Deserialize)]
+pub struct ChecklistOrder {
+    pub ids: Vec<i64>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Default)]
+pub struct ChecklistProgress {
+    pub checked: i64,
+    pub total: i64,
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::validation::{Validate, Validator};
+
+/// Deepest level a reply can nest at, top-level comments are level 0.
+pub const MAX_REPLY_DEPTH: usize = 10;
+
+#[derive(Serialize)]
+pub struct Comment {
+    pub id: i64,
+    pub task_id: i64,
+    pub user_id: i64,
+    pub author: String,
+    pub parent_id: Option<i64>,
+    /// Markdown source, `None` once the comment is deleted.
+    pub body: Option<String>,
+    pub deleted: bool,
+    pub created_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub replies: Vec<Comment>,
+}
+
+#[derive(Deserialize)]
+pub struct NewComment {
+    pub body: String,
+    pub parent_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdatedComment {
+    pub body: String,
+}
+
+impl Validate for NewComment {
+    fn validate(&mut self, v: &mut Validator) {
+        v.body("body", &self.body);
+    }
+}
+
+impl Validate for UpdatedComment {
+    fn validate(&mut self, v: &mut Validator) {
+        v.body("body", &self.body);
+    }
+}
@@ -1,6 +1,219 @@
-use serde::{Deserialize, Serialize};
+use rocket::{
+    http::Status,
+    request::Request,
+    response::{self, status, Responder},
+    serde::json::Json,
+};
+use serde::Serialize;
+use sqlx::error::ErrorKind;
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ErrorResponse<'a> {
-    pub error: &'a str,
+use crate::{
+    guards::request_id::RequestId,
+    repository::RepoError,
+    telemetry::{metrics::Metrics, request::RequestTrace},
+    utils::filter::FilterError,
+};
+
+/// One invalid field of a request, `code` is stable and meant for clients.
+#[derive(Serialize, Debug, Clone)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub code: &'static str,
+    pub message: &'static str,
+}
+
+/// The body of every error response. `error` is the human readable message,
+/// `code` does not change between releases.
+#[derive(Serialize, Debug)]
+pub struct ErrorResponse {
+    pub error: &'static str,
+    pub code: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<FieldError>,
+    /// Character offset of the offending input of a filter query.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+    pub request_id: String,
+}
+
+/// Errors returned by the handlers. Variants taking a code are the ones clients
+/// need to tell apart within a status, e.g. `project_archived` and `task_blocked`.
+#[derive(Debug)]
+pub enum AppError {
+    BadRequest(&'static str, &'static str),
+    Unauthorized(&'static str),
+    Forbidden(&'static str),
+    NotFound(&'static str),
+    Conflict(&'static str, &'static str),
+    PayloadTooLarge(&'static str),
+    UnsupportedMediaType(&'static str),
+    /// The request is well-formed but some of its fields are not acceptable.
+    Validation(Vec<FieldError>),
+    /// A filter query that could not be parsed.
+    InvalidQuery(FilterError),
+    Database(sqlx::Error),
+    Internal(&'static str),
+    /// An error raised by Rocket itself, e.g. a failing guard or an unknown route.
+    Http(Status),
+}
+
+impl AppError {
+    /// A single invalid field.
+    pub fn invalid(field: &'static str, code: &'static str, message: &'static str) -> Self {
+        AppError::Validation(vec![FieldError {
+            field,
+            code,
+            message,
+        }])
+    }
+
+    /// Maps a repository error, `not_found` is the message for a missing row.
+    pub fn from_repo(error: RepoError, not_found: &'static str) -> Self {
+        match error {
+            RepoError::NotFound => AppError::NotFound(not_found),
+            RepoError::Conflict(code, message) => AppError::Conflict(code, message),
+            RepoError::Invalid(code, message) => AppError::BadRequest(code, message),
+            RepoError::Database(e) => AppError::Database(e),
+            RepoError::Storage(e) => {
+                tracing::error!(error = ?e, "attachment storage failed");
+                AppError::Internal("Storage Error")
+            }
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.parts().1
+    }
+
+    pub fn message(&self) -> &'static str {
+        self.parts().2
+    }
+
+    fn parts(&self) -> (Status, &'static str, &'static str) {
+        match self {
+            AppError::BadRequest(code, message) => (Status::BadRequest, *code, *message),
+            AppError::Unauthorized(message) => (Status::Unauthorized, "unauthorized", *message),
+            AppError::Forbidden(message) => (Status::Forbidden, "forbidden", *message),
+            AppError::NotFound(message) => (Status::NotFound, "not_found", *message),
+            AppError::Conflict(code, message) => (Status::Conflict, *code, *message),
+            AppError::PayloadTooLarge(message) => {
+                (Status::PayloadTooLarge, "payload_too_large", *message)
+            }
+            AppError::UnsupportedMediaType(message) => (
+                Status::UnsupportedMediaType,
+                "unsupported_media_type",
+                *message,
+            ),
+            AppError::Validation(_) => (
+                Status::UnprocessableEntity,
+                "validation_failed",
+                "Some fields are invalid",
+            ),
+            AppError::InvalidQuery(error) => (Status::BadRequest, "invalid_query", error.message),
+            AppError::Database(sqlx::Error::RowNotFound) => {
+                (Status::NotFound, "not_found", "Not found")
+            }
+            AppError::Database(sqlx::Error::Database(e)) => match e.kind() {
+                ErrorKind::UniqueViolation => (
+                    Status::Conflict,
+                    "already_exists",
+                    "A record with the same values already exists",
+                ),
+                ErrorKind::ForeignKeyViolation => (
+                    Status::Conflict,
+                    "reference_conflict",
+                    "A referenced record does not exist or is still in use",
+                ),
+                ErrorKind::NotNullViolation | ErrorKind::CheckViolation => (
+                    Status::BadRequest,
+                    "constraint_violation",
+                    "The request violates a database constraint",
+                ),
+                _ => (
+                    Status::InternalServerError,
+                    "database_error",
+                    "Database Error",
+                ),
+            },
+            AppError::Database(_) => (
+                Status::InternalServerError,
+                "database_error",
+                "Database Error",
+            ),
+            AppError::Internal(message) => {
+                (Status::InternalServerError, "internal_error", *message)
+            }
+            AppError::Http(status) => match status.code {
+                400 => (
+                    *status,
+                    "bad_request",
+                    "The request could not be understood",
+                ),
+                401 => (*status, "unauthorized", "Authentication required"),
+                403 => (*status, "forbidden", "Permission denied"),
+                404 => (*status, "not_found", "Not found"),
+                413 => (
+                    *status,
+                    "payload_too_large",
+                    "The request body is too large",
+                ),
+                422 => (
+                    *status,
+                    "unprocessable_body",
+                    "The request body does not have the expected shape",
+                ),
+                code if code < 500 => (*status, "bad_request", "The request was rejected"),
+                _ => (*status, "internal_error", "Internal Server Error"),
+            },
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(e: sqlx::Error) -> Self {
+        AppError::Database(e)
+    }
+}
+
+impl From<RepoError> for AppError {
+    fn from(e: RepoError) -> Self {
+        AppError::from_repo(e, "Not found")
+    }
+}
+
+impl<'r> Responder<'r, 'static> for AppError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        let (status, code, message) = self.parts();
+        let request_id = RequestId::of(request).0.clone();
+        if let (AppError::Database(sqlx::Error::PoolTimedOut), Some(metrics)) =
+            (&self, request.rocket().state::<Metrics>())
+        {
+            metrics.pool_timeouts.inc();
+        }
+        if status.code >= 500 {
+            RequestTrace::of(request)
+                .span
+                .in_scope(|| tracing::error!(code, error = ?self, "request error"));
+        }
+
+        let position = match &self {
+            AppError::InvalidQuery(error) => Some(error.position),
+            _ => None,
+        };
+        let details = match self {
+            AppError::Validation(details) => details,
+            _ => Vec::new(),
+        };
+        status::Custom(
+            status,
+            Json(ErrorResponse {
+                error: message,
+                code,
+                details,
+                position,
+                request_id,
+            }),
+        )
+        .respond_to(request)
+    }
 }
@@ -1,5 +1,14 @@
+pub mod activity;
+pub mod attachment;
+pub mod bulk;
+pub mod checklist;
+pub mod comment;
 pub mod error;
 pub mod pagination;
 pub mod project;
+pub mod search;
 pub mod tasks;
+pub mod time_entry;
+pub mod trash;
 pub mod user;
+pub mod view;
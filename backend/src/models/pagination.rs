@@ -1,4 +1,19 @@
+use rocket::FromForm;
+
+/// Keeps `offset()` far from overflowing, even with the largest page size.
+const MAX_PAGE: isize = 1_000_000;
+
+/// `?page=&size=` query parameters, pages start at 1.
+#[derive(FromForm)]
 pub struct Pagination {
+    #[field(default = 1, validate = range(1..=MAX_PAGE))]
     pub page: i64,
+    #[field(default = 50, validate = range(1..=200))]
     pub size: i64,
 }
+
+impl Pagination {
+    pub fn offset(&self) -> i64 {
+        (self.page - 1) * self.size
+    }
+}
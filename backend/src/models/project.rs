@@ -1,12 +1,16 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::tasks::Tasks;
+use super::tasks::{TaskStatus, Tasks};
+use crate::utils::validation::{Validate, Validator, MAX_VARCHAR};
 
 #[derive(Deserialize, Serialize)]
 pub struct Project {
     pub id: i64,
     pub user_id: i64,
     pub name: String,
+    /// Set while the project is archived and read-only.
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Deserialize)]
@@ -14,8 +18,54 @@ pub struct NewProject {
     pub name: String,
 }
 
+impl Validate for NewProject {
+    fn validate(&mut self, v: &mut Validator) {
+        v.text("name", &mut self.name, MAX_VARCHAR);
+    }
+}
+
 #[derive(Serialize)]
 pub struct ProjectData {
     pub items: usize,
     pub data: Vec<Tasks>,
 }
+
+#[derive(Serialize)]
+pub struct ProjectSchedule {
+    /// Every task of the project, each one after all of its dependencies.
+    pub order: Vec<Tasks>,
+    /// Ids of the longest chain of dependent tasks, first to last.
+    pub critical_path: Vec<i64>,
+}
+
+#[derive(Serialize)]
+pub struct StatusSummary {
+    pub status: TaskStatus,
+    pub tasks: i64,
+    pub story_points: i64,
+    pub estimate_hours: f64,
+}
+
+/// Task counts and estimate totals of a project, overall and per status.
+#[derive(Serialize)]
+pub struct ProjectSummary {
+    pub id: i64,
+    pub name: String,
+    pub tasks: i64,
+    pub story_points: i64,
+    pub estimate_hours: f64,
+    pub by_status: Vec<StatusSummary>,
+}
+
+#[derive(Serialize)]
+pub struct BoardColumn {
+    pub status: TaskStatus,
+    pub tasks: Vec<Tasks>,
+}
+
+/// Every column of a project board, each one in manual order.
+#[derive(Serialize)]
+pub struct Board {
+    pub project_id: i64,
+    pub columns: Vec<BoardColumn>,
+}
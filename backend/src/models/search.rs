@@ -0,0 +1,51 @@
+use rocket::FromFormField;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, FromFormField, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DocKind {
+    #[field(value = "task")]
+    Task,
+    #[field(value = "project")]
+    Project,
+    #[field(value = "comment")]
+    Comment,
+}
+
+impl DocKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocKind::Task => "task",
+            DocKind::Project => "project",
+            DocKind::Comment => "comment",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "task" => Some(DocKind::Task),
+            "project" => Some(DocKind::Project),
+            "comment" => Some(DocKind::Comment),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct SearchHit {
+    pub kind: DocKind,
+    pub id: i64,
+    pub project_id: i64,
+    pub task_id: Option<i64>,
+    pub title: String,
+    /// HTML escaped excerpt with the matched terms wrapped in `<mark>`.
+    pub snippet: String,
+    pub score: f32,
+}
+
+#[derive(Serialize)]
+pub struct SearchResults {
+    pub page: i64,
+    pub size: i64,
+    pub hits: Vec<SearchHit>,
+}
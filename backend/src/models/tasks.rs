@@ -1,23 +1,333 @@
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use rocket::FromFormField;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::checklist::ChecklistProgress;
+use crate::utils::validation::{Validate, Validator, MAX_VARCHAR};
+
+#[derive(Deserialize, Serialize, FromFormField, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    #[default]
+    #[field(value = "todo")]
+    Todo,
+    #[field(value = "in_progress")]
+    InProgress,
+    #[field(value = "done")]
+    Done,
+}
+
+impl TaskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "todo",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Done => "done",
+        }
+    }
+}
+
+impl FromStr for TaskStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "todo" => Ok(TaskStatus::Todo),
+            "in_progress" => Ok(TaskStatus::InProgress),
+            "done" => Ok(TaskStatus::Done),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(
+    Deserialize,
+    Serialize,
+    FromFormField,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Debug,
+    Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskPriority {
+    #[field(value = "low")]
+    Low,
+    #[default]
+    #[field(value = "medium")]
+    Medium,
+    #[field(value = "high")]
+    High,
+    #[field(value = "urgent")]
+    Urgent,
+}
+
+impl TaskPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskPriority::Low => "low",
+            TaskPriority::Medium => "medium",
+            TaskPriority::High => "high",
+            TaskPriority::Urgent => "urgent",
+        }
+    }
+}
+
+impl FromStr for TaskPriority {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(TaskPriority::Low),
+            "medium" => Ok(TaskPriority::Medium),
+            "high" => Ok(TaskPriority::High),
+            "urgent" => Ok(TaskPriority::Urgent),
+            _ => Err(()),
+        }
+    }
+}
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Tasks {
     pub id: i64,
     pub user_id: i64,
     pub project_id: i64,
+    pub parent_id: Option<i64>,
     pub title: String,
     pub description: Option<String>,
+    pub status: TaskStatus,
+    pub priority: TaskPriority,
+    pub due_date: Option<DateTime<Utc>>,
+    /// RFC 5545 recurrence rule, e.g. `FREQ=WEEKLY;BYDAY=MO`.
+    pub recurrence: Option<String>,
+    pub story_points: Option<i64>,
+    pub estimate_hours: Option<f64>,
+    /// Position within its status column on the board, tasks sort by it as plain strings.
+    pub rank: String,
+    pub checklist: ChecklistProgress,
     pub created_at: DateTime<Utc>,
 }
+/// Order of a task listing, ties are broken by id.
+#[derive(Deserialize, Serialize, FromFormField, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSort {
+    #[default]
+    #[field(value = "created")]
+    Created,
+    /// Tasks without a due date come last.
+    #[field(value = "due_date")]
+    DueDate,
+    #[field(value = "priority")]
+    Priority,
+    #[field(value = "title")]
+    Title,
+    /// Board order, grouped by project.
+    #[field(value = "rank")]
+    Rank,
+}
+
+impl TaskSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskSort::Created => "created",
+            TaskSort::DueDate => "due_date",
+            TaskSort::Priority => "priority",
+            TaskSort::Title => "title",
+            TaskSort::Rank => "rank",
+        }
+    }
+}
+
+impl FromStr for TaskSort {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created" => Ok(TaskSort::Created),
+            "due_date" => Ok(TaskSort::DueDate),
+            "priority" => Ok(TaskSort::Priority),
+            "title" => Ok(TaskSort::Title),
+            "rank" => Ok(TaskSort::Rank),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What a task listing covers and how it is ordered.
+pub struct ListOptions {
+    /// Limits the listing to a single project.
+    pub project_id: Option<i64>,
+    /// Includes the tasks of archived projects.
+    pub include_archived: bool,
+    pub sort: TaskSort,
+    pub descending: bool,
+}
+
+/// One page of the tasks matching a filter query.
+#[derive(Serialize)]
+pub struct TaskPage {
+    pub page: i64,
+    pub size: i64,
+    pub total: i64,
+    pub data: Vec<Tasks>,
+}
+
 #[derive(Deserialize)]
 pub struct NewTask {
     pub title: String,
     pub description: Option<String>,
+    pub parent_id: Option<i64>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub recurrence: Option<String>,
+    pub story_points: Option<i64>,
+    pub estimate_hours: Option<f64>,
+}
+
+impl Validate for NewTask {
+    fn validate(&mut self, v: &mut Validator) {
+        v.text("title", &mut self.title, MAX_VARCHAR);
+        v.optional_body("description", self.description.as_deref());
+        v.optional_text("recurrence", &mut self.recurrence, MAX_VARCHAR);
+        validate_estimates(v, self.story_points, self.estimate_hours);
+    }
+}
+
+/// A field that can be cleared: missing is `None` and keeps the current value,
+/// `null` is `Some(None)` and clears it.
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
+
+/// Fields left out keep their value, the optional ones are cleared with `null`.
 #[derive(Deserialize)]
 pub struct UpdatedTask {
     pub title: Option<String>,
-    pub description: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub description: Option<Option<String>>,
     pub project_id: Option<i64>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub due_date: Option<Option<DateTime<Utc>>>,
+    /// An empty string stops the recurrence.
+    pub recurrence: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub story_points: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub estimate_hours: Option<Option<f64>>,
+}
+
+impl Validate for UpdatedTask {
+    fn validate(&mut self, v: &mut Validator) {
+        if let Some(title) = &mut self.title {
+            v.text("title", title, MAX_VARCHAR);
+        }
+        v.optional_body(
+            "description",
+            self.description.as_ref().and_then(Option::as_deref),
+        );
+        v.optional_text("recurrence", &mut self.recurrence, MAX_VARCHAR);
+        validate_estimates(
+            v,
+            self.story_points.flatten(),
+            self.estimate_hours.flatten(),
+        );
+    }
+}
+
+fn validate_estimates(v: &mut Validator, story_points: Option<i64>, estimate_hours: Option<f64>) {
+    v.check(
+        story_points.is_none_or(|points| points >= 0),
+        "story_points",
+        "negative",
+        "Estimates cannot be negative",
+    );
+    v.check(
+        estimate_hours.is_none_or(|hours| hours.is_finite() && hours >= 0.0),
+        "estimate_hours",
+        "negative",
+        "Estimates cannot be negative",
+    );
+}
+
+/// Body of `PUT /task/<id>/move`. A `None` parent turns the task into a root task.
+#[derive(Deserialize)]
+pub struct MoveTask {
+    pub parent_id: Option<i64>,
+}
+
+/// Body of `PUT /task/<id>/board`. The task lands in the `status` column right after
+/// `after` and/or right before `before`, or at the end of the column without either.
+#[derive(Deserialize)]
+pub struct BoardMove {
+    pub status: TaskStatus,
+    pub after: Option<i64>,
+    pub before: Option<i64>,
+}
+
+/// What happens to the subtasks of a deleted task.
+#[derive(FromFormField, Clone, Copy, PartialEq, Eq)]
+pub enum SubtaskPolicy {
+    /// Attach the children to the deleted task's own parent.
+    #[field(value = "reparent")]
+    Reparent,
+    /// Delete the whole subtree.
+    #[field(value = "delete")]
+    Delete,
+}
+
+/// Completion progress rolled up over every descendant of a task.
+#[derive(Serialize)]
+pub struct TaskProgress {
+    pub done: i64,
+    pub total: i64,
+}
+
+#[derive(Serialize)]
+pub struct SubtaskData {
+    pub items: usize,
+    pub progress: TaskProgress,
+    pub data: Vec<Tasks>,
+}
+
+#[derive(Deserialize)]
+pub struct NewLabel {
+    pub label: String,
+}
+
+impl Validate for NewLabel {
+    fn validate(&mut self, v: &mut Validator) {
+        v.label("label", &mut self.label);
+    }
+}
+
+#[derive(Deserialize)]
+pub struct NewDependency {
+    pub depends_on: i64,
+}
+
+#[derive(Serialize)]
+pub struct TaskSummary {
+    pub id: i64,
+    pub title: String,
+    pub status: TaskStatus,
+}
+
+/// Both directions of the dependency graph around a task.
+#[derive(Serialize)]
+pub struct TaskDependencies {
+    pub blocked_by: Vec<TaskSummary>,
+    pub blocks: Vec<TaskSummary>,
 }
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use rocket::FromFormField;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::validation::{Validate, Validator};
+
+#[derive(Serialize)]
+pub struct TimeEntry {
+    pub id: i64,
+    pub task_id: i64,
+    pub user_id: i64,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the timer is running.
+    pub ended_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct NewTimeEntry {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub note: Option<String>,
+}
+
+impl Validate for NewTimeEntry {
+    fn validate(&mut self, v: &mut Validator) {
+        v.check(
+            self.ended_at > self.started_at,
+            "ended_at",
+            "before_start",
+            "ended_at must be after started_at",
+        );
+        v.optional_body("note", self.note.as_deref());
+    }
+}
+
+#[derive(FromFormField, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportGrouping {
+    #[field(value = "project")]
+    Project,
+    #[default]
+    #[field(value = "task")]
+    Task,
+    #[field(value = "user")]
+    User,
+    #[field(value = "day")]
+    Day,
+}
+
+#[derive(FromFormField, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    #[default]
+    #[field(value = "json")]
+    Json,
+    #[field(value = "csv")]
+    Csv,
+}
+
+#[derive(Serialize)]
+pub struct TimeReportRow {
+    pub key: String,
+    pub label: String,
+    pub seconds: i64,
+}
+
+#[derive(Serialize)]
+pub struct TimeReport {
+    pub group_by: ReportGrouping,
+    pub total_seconds: i64,
+    pub rows: Vec<TimeReportRow>,
+}
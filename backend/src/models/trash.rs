@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct TrashedProject {
+    pub id: i64,
+    pub name: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct TrashedTask {
+    pub id: i64,
+    pub project_id: i64,
+    pub title: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// Everything a user deleted that has not been purged yet. Tasks trashed along
+/// with a project or a parent task only show up through that project or parent.
+#[derive(Serialize)]
+pub struct Trash {
+    pub projects: Vec<TrashedProject>,
+    pub tasks: Vec<TrashedTask>,
+}
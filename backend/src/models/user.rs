@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::utils::validation::{Validate, Validator, MAX_VARCHAR};
+
 #[derive(Serialize, Deserialize)]
 pub struct User {
     pub id: i64,
@@ -19,3 +21,49 @@ pub struct NewUser {
     pub username: String,
     pub password: String,
 }
+
+/// Body of the 202 answered to every sign-up in `SignupMode::Email`.
+#[derive(Serialize)]
+pub struct SignUpAccepted {
+    pub message: &'static str,
+}
+
+/// Body of `/auth/confirm`, the token emailed on sign-up.
+#[derive(Deserialize)]
+pub struct SignUpConfirmation {
+    pub token: String,
+}
+
+impl Validate for UserCredential {
+    fn validate(&mut self, v: &mut Validator) {
+        // Matched against the stored, normalized address
+        v.text("email", &mut self.email, MAX_VARCHAR);
+        self.email = self.email.to_lowercase();
+        v.check(
+            !self.password.is_empty(),
+            "password",
+            "required",
+            "Cannot be empty",
+        );
+    }
+}
+
+impl Validate for NewUser {
+    fn validate(&mut self, v: &mut Validator) {
+        v.email("email", &mut self.email);
+        v.text("username", &mut self.username, MAX_VARCHAR);
+        // Passwords are taken as typed, spaces included
+        v.check(
+            !self.password.is_empty(),
+            "password",
+            "required",
+            "Cannot be empty",
+        );
+        v.check(
+            self.password.chars().count() <= MAX_VARCHAR,
+            "password",
+            "too_long",
+            "Exceeds the maximum length",
+        );
+    }
+}
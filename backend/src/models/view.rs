@@ -0,0 +1,74 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::tasks::TaskSort;
+use crate::utils::validation::{Validate, Validator, MAX_QUERY, MAX_VARCHAR};
+
+/// How clients render a saved view, the server only stores it.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayMode {
+    #[default]
+    List,
+    Board,
+    Table,
+}
+
+impl DisplayMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisplayMode::List => "list",
+            DisplayMode::Board => "board",
+            DisplayMode::Table => "table",
+        }
+    }
+}
+
+impl FromStr for DisplayMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "list" => Ok(DisplayMode::List),
+            "board" => Ok(DisplayMode::Board),
+            "table" => Ok(DisplayMode::Table),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct SavedView {
+    pub id: i64,
+    pub user_id: i64,
+    /// Set for views shared within a project, their tasks are limited to it.
+    pub project_id: Option<i64>,
+    pub name: String,
+    /// Filter query, e.g. `label:bug priority>=high -status:done`.
+    pub query: String,
+    pub sort: TaskSort,
+    pub descending: bool,
+    pub display: DisplayMode,
+    pub include_archived: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct NewSavedView {
+    pub name: String,
+    pub project_id: Option<i64>,
+    pub query: Option<String>,
+    pub sort: Option<TaskSort>,
+    pub descending: Option<bool>,
+    pub display: Option<DisplayMode>,
+    pub include_archived: Option<bool>,
+}
+
+impl Validate for NewSavedView {
+    fn validate(&mut self, v: &mut Validator) {
+        v.text("name", &mut self.name, MAX_VARCHAR);
+        v.optional_text("query", &mut self.query, MAX_QUERY);
+    }
+}
@@ -0,0 +1,470 @@
+//! Storage behind the handlers. The SQL databases share one implementation in
+//! `sql/`, compiled once per database by `mysql.rs`, `sqlite.rs` and `postgres.rs`
+//! which only provide the dialect: placeholders, ids of inserted rows and dates.
+
+use std::{fmt, io, sync::Arc};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    db::{SqliteDB, DB},
+    models::{
+        activity::{ActivityPage, FieldChange},
+        attachment::Attachment,
+        bulk::{BulkOperation, TaskFilter},
+        checklist::ChecklistItem,
+        comment::{Comment, NewComment},
+        pagination::Pagination,
+        project::{Project, StatusSummary},
+        tasks::{
+            BoardMove, ListOptions, SubtaskPolicy, TaskDependencies, TaskPage, TaskProgress, Tasks,
+        },
+        time_entry::{NewTimeEntry, TimeEntry},
+        trash::Trash,
+        user::User,
+        view::{NewSavedView, SavedView},
+    },
+    search::{SearchBackend, SearchDocument, SearchError},
+    storage::FileStorage,
+    utils::filter::Filter,
+};
+
+pub mod mysql;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod sqlite;
+
+#[derive(Debug)]
+pub enum RepoError {
+    /// The row does not exist, is deleted or belongs to someone else.
+    NotFound,
+    /// The change contradicts the stored data, e.g. it would close a dependency
+    /// cycle. Carries a stable code and a message.
+    Conflict(&'static str, &'static str),
+    /// The request does not fit the stored data, e.g. an incomplete order.
+    Invalid(&'static str, &'static str),
+    Database(sqlx::Error),
+    /// Attachment content could not be read or written.
+    Storage(io::Error),
+}
+
+impl fmt::Display for RepoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepoError::NotFound => write!(f, "row not found"),
+            RepoError::Conflict(_, message) | RepoError::Invalid(_, message) => {
+                write!(f, "{}", message)
+            }
+            RepoError::Database(e) => write!(f, "database error: {}", e),
+            RepoError::Storage(e) => write!(f, "storage error: {}", e),
+        }
+    }
+}
+
+impl From<RepoError> for SearchError {
+    fn from(e: RepoError) -> Self {
+        SearchError::Backend(e.to_string())
+    }
+}
+
+impl From<sqlx::Error> for RepoError {
+    fn from(e: sqlx::Error) -> Self {
+        match e {
+            sqlx::Error::RowNotFound => RepoError::NotFound,
+            e => RepoError::Database(e),
+        }
+    }
+}
+
+/// A user together with the password hash, never sent to clients.
+pub struct UserAccount {
+    pub user: User,
+    pub password: String,
+}
+
+/// A sign-up waiting for its address to be confirmed, `password` is already
+/// hashed and `token_hash` is the SHA-256 of the emailed token.
+pub struct PendingSignup {
+    pub email: String,
+    pub username: String,
+    pub password: String,
+    pub token_hash: String,
+}
+
+#[rocket::async_trait]
+pub trait UserRepository: Send + Sync {
+    /// Stores a user, `password` is already hashed.
+    async fn create(&self, email: &str, username: &str, password: &str) -> Result<User, RepoError>;
+    async fn find_by_email(&self, email: &str) -> Result<Option<UserAccount>, RepoError>;
+    /// Keeps a sign-up until it is confirmed, pending sign-ups created before
+    /// `expired_before` are dropped along the way.
+    async fn create_pending(
+        &self,
+        signup: &PendingSignup,
+        expired_before: DateTime<Utc>,
+    ) -> Result<(), RepoError>;
+    /// Creates the account of the pending sign-up with this token, if it was
+    /// created at or after `created_after`, and drops every pending sign-up of
+    /// its address.
+    async fn confirm_pending(
+        &self,
+        token_hash: &str,
+        created_after: DateTime<Utc>,
+    ) -> Result<User, RepoError>;
+}
+
+/// Projects are always looked up for their owner.
+#[rocket::async_trait]
+pub trait ProjectRepository: Send + Sync {
+    async fn create(&self, user_id: i64, name: &str) -> Result<Project, RepoError>;
+    async fn find(&self, id: i64, user_id: i64) -> Result<Project, RepoError>;
+    /// Whether a live project is archived and read-only.
+    async fn archived(&self, id: i64, user_id: i64) -> Result<bool, RepoError>;
+    async fn list(&self, user_id: i64, archived: bool) -> Result<Vec<Project>, RepoError>;
+    /// Every live task of the project, in board order.
+    async fn tasks(&self, id: i64, user_id: i64) -> Result<Vec<Tasks>, RepoError>;
+    /// Dependency edges `(task, depends on)` between live tasks of the project.
+    async fn dependencies(&self, id: i64, user_id: i64) -> Result<Vec<(i64, i64)>, RepoError>;
+    /// Totals of the statuses that have tasks.
+    async fn status_totals(&self, id: i64, user_id: i64) -> Result<Vec<StatusSummary>, RepoError>;
+    async fn set_archived(&self, id: i64, archived: bool) -> Result<(), RepoError>;
+    async fn rename(&self, id: i64, user_id: i64, name: &str) -> Result<(), RepoError>;
+    /// Moves the project and its tasks to the trash.
+    async fn trash(&self, id: i64, user_id: i64) -> Result<(), RepoError>;
+}
+
+/// Writes of a task also append to its activity log, in the same transaction.
+#[rocket::async_trait]
+pub trait TaskRepository: Send + Sync {
+    async fn find(&self, id: i64, user_id: i64) -> Result<Tasks, RepoError>;
+    /// Direct subtasks of `id`, in board order.
+    async fn children(&self, id: i64, user_id: i64) -> Result<Vec<Tasks>, RepoError>;
+    /// Completion of every descendant of `id`.
+    async fn progress(&self, id: i64) -> Result<TaskProgress, RepoError>;
+    /// Ids of every live descendant of `id`, excluding `id` itself.
+    async fn subtree(&self, id: i64) -> Result<Vec<i64>, RepoError>;
+    /// Number of unfinished tasks `id` depends on.
+    async fn open_blockers(&self, id: i64) -> Result<i64, RepoError>;
+    /// Inserts `task` at the bottom of its column, returns it with its id and rank.
+    async fn create(&self, task: Tasks) -> Result<Tasks, RepoError>;
+    /// Saves `task` over `previous` and returns it. A task changing column goes to
+    /// its bottom, a task changing project takes its subtree along and completing
+    /// a recurring task schedules the next occurrence.
+    async fn update(
+        &self,
+        previous: &Tasks,
+        task: Tasks,
+        changes: &[FieldChange],
+    ) -> Result<Tasks, RepoError>;
+    /// Moves `task` under `parent_id` in `project_id`, the subtree follows it into
+    /// the project. Moving a task under itself or a descendant is a conflict.
+    async fn reparent(
+        &self,
+        task: &Tasks,
+        parent_id: Option<i64>,
+        project_id: i64,
+        changes: &[FieldChange],
+    ) -> Result<(), RepoError>;
+    /// Moves `task` to the trash, its subtasks are handled by `subtasks`.
+    async fn trash(&self, task: &Tasks, subtasks: Option<SubtaskPolicy>) -> Result<(), RepoError>;
+    /// Puts `task` into the `target` column at the requested place and returns it.
+    /// Fails with `Invalid` when a neighbour is not in that column and with
+    /// `Conflict` when the neighbours are no longer adjacent.
+    async fn move_on_board(&self, task: &Tasks, target: &BoardMove) -> Result<Tasks, RepoError>;
+    /// One page of the live tasks of `user_id` matching `filter`.
+    async fn query(
+        &self,
+        user_id: i64,
+        filter: &Filter,
+        options: &ListOptions,
+        pagination: &Pagination,
+    ) -> Result<TaskPage, RepoError>;
+    /// Ids of the live tasks in unarchived projects matching `filter`, at most `limit`.
+    async fn select(
+        &self,
+        user_id: i64,
+        filter: &TaskFilter,
+        limit: i64,
+    ) -> Result<Vec<i64>, RepoError>;
+    /// Applies `operation` to each task in order, within one transaction that is
+    /// only committed when every item succeeded. Returns the outcome of each item.
+    async fn bulk(
+        &self,
+        user_id: i64,
+        ids: &[i64],
+        operation: &BulkOperation,
+    ) -> Result<Vec<Result<(), RepoError>>, RepoError>;
+    /// Creates the next occurrence of the recurring tasks past due in unarchived
+    /// projects, the first one still ahead, returns how many were created.
+    async fn roll_overdue(&self) -> Result<usize, RepoError>;
+}
+
+/// Comments are listed flat, replies point at their parent.
+#[rocket::async_trait]
+pub trait CommentRepository: Send + Sync {
+    /// Every comment of the task in the order they were written, deleted ones
+    /// without their body.
+    async fn list(&self, task_id: i64) -> Result<Vec<Comment>, RepoError>;
+    /// Fails with `NotFound` when the parent is not a live comment of the task.
+    async fn create(
+        &self,
+        task_id: i64,
+        user_id: i64,
+        comment: &NewComment,
+    ) -> Result<Comment, RepoError>;
+    /// Only the author of a live comment can edit it.
+    async fn edit(
+        &self,
+        id: i64,
+        task_id: i64,
+        user_id: i64,
+        body: &str,
+    ) -> Result<Comment, RepoError>;
+    /// Soft delete by the author, replies keep pointing at the comment.
+    async fn delete(&self, id: i64, task_id: i64, user_id: i64) -> Result<(), RepoError>;
+}
+
+/// Attachment rows, their content lives in the storage backend.
+#[rocket::async_trait]
+pub trait AttachmentRepository: Send + Sync {
+    async fn list(&self, task_id: i64) -> Result<Vec<Attachment>, RepoError>;
+    /// Inserts `attachment` and returns it with its id, `content` is stored
+    /// unless another attachment already shares it.
+    async fn create(&self, attachment: Attachment, content: &[u8])
+        -> Result<Attachment, RepoError>;
+    async fn find(&self, id: i64, task_id: i64) -> Result<Attachment, RepoError>;
+    /// Deletes the row, then its content when no other attachment refers to it.
+    async fn delete(&self, id: i64, task_id: i64) -> Result<(), RepoError>;
+    /// Deletes the content stored under `sha256` if no attachment refers to it
+    /// anymore, e.g. once the trash is purged.
+    async fn release(&self, sha256: &str) -> Result<(), RepoError>;
+}
+
+#[rocket::async_trait]
+pub trait ChecklistRepository: Send + Sync {
+    async fn list(&self, task_id: i64) -> Result<Vec<ChecklistItem>, RepoError>;
+    /// Appends an unchecked item to the list.
+    async fn add(&self, task_id: i64, text: &str) -> Result<ChecklistItem, RepoError>;
+    /// Fails with `Invalid` unless `ids` lists every item exactly once.
+    async fn reorder(&self, task_id: i64, ids: &[i64]) -> Result<Vec<ChecklistItem>, RepoError>;
+    async fn toggle(&self, id: i64, task_id: i64) -> Result<ChecklistItem, RepoError>;
+    async fn delete(&self, id: i64, task_id: i64) -> Result<(), RepoError>;
+}
+
+/// Selects the time entries of a report, `from` and `until` bound the start of
+/// an entry.
+pub struct TimeQuery {
+    pub project_id: Option<i64>,
+    pub task_id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub from: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// A time entry along with everything a report can group it by.
+pub struct TrackedTime {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub task_id: i64,
+    pub title: String,
+    pub project_id: i64,
+    pub project_name: String,
+    pub user_id: i64,
+    pub username: String,
+}
+
+#[rocket::async_trait]
+pub trait TimeEntryRepository: Send + Sync {
+    async fn list(&self, task_id: i64) -> Result<Vec<TimeEntry>, RepoError>;
+    async fn create(
+        &self,
+        task_id: i64,
+        user_id: i64,
+        entry: &NewTimeEntry,
+    ) -> Result<TimeEntry, RepoError>;
+    /// Only the user who tracked an entry can delete it.
+    async fn delete(&self, id: i64, task_id: i64, user_id: i64) -> Result<(), RepoError>;
+    /// Fails with `Conflict` while another timer of the user is running.
+    async fn start(&self, task_id: i64, user_id: i64) -> Result<TimeEntry, RepoError>;
+    async fn stop(&self, task_id: i64, user_id: i64) -> Result<TimeEntry, RepoError>;
+    /// Entries on live tasks of the projects of `owner_id` matching `query`.
+    async fn tracked(
+        &self,
+        owner_id: i64,
+        query: &TimeQuery,
+    ) -> Result<Vec<TrackedTime>, RepoError>;
+}
+
+/// Label changes are logged in the activity of the task.
+#[rocket::async_trait]
+pub trait LabelRepository: Send + Sync {
+    /// Labels of the task in alphabetical order.
+    async fn list(&self, task_id: i64) -> Result<Vec<String>, RepoError>;
+    /// Adds the label unless the task has it already, returns every label.
+    async fn add(&self, task: &Tasks, user_id: i64, label: &str) -> Result<Vec<String>, RepoError>;
+    async fn remove(&self, task: &Tasks, user_id: i64, label: &str) -> Result<(), RepoError>;
+}
+
+#[rocket::async_trait]
+pub trait DependencyRepository: Send + Sync {
+    async fn list(&self, task_id: i64) -> Result<TaskDependencies, RepoError>;
+    /// Makes `task_id` depend on `depends_on`, fails with `Conflict` when the edge
+    /// would close a cycle.
+    async fn add(&self, task_id: i64, depends_on: i64) -> Result<(), RepoError>;
+    async fn remove(&self, task_id: i64, depends_on: i64) -> Result<(), RepoError>;
+}
+
+/// The activity log, newest entries first.
+#[rocket::async_trait]
+pub trait ActivityRepository: Send + Sync {
+    async fn task_history(
+        &self,
+        task_id: i64,
+        pagination: &Pagination,
+    ) -> Result<ActivityPage, RepoError>;
+    /// Activity of every task in the project, including the tasks deleted from it
+    /// and the ones moved out of it.
+    async fn project_activity(
+        &self,
+        project_id: i64,
+        pagination: &Pagination,
+    ) -> Result<ActivityPage, RepoError>;
+}
+
+#[rocket::async_trait]
+pub trait TrashRepository: Send + Sync {
+    async fn list(&self, user_id: i64) -> Result<Trash, RepoError>;
+    /// Restores a task and the subtasks deleted along with it. Fails with
+    /// `Conflict` while its project is in the trash or archived.
+    async fn restore_task(&self, id: i64, user_id: i64) -> Result<(), RepoError>;
+    /// Restores a project with the tasks deleted along with it.
+    async fn restore_project(&self, id: i64, user_id: i64) -> Result<Project, RepoError>;
+    /// Permanently deletes what went to the trash before `cutoff`. Returns the
+    /// content keys of the attachments deleted with it.
+    async fn purge(&self, cutoff: DateTime<Utc>) -> Result<Vec<String>, RepoError>;
+}
+
+/// Views are visible to their owner and, once shared in a project, to the owner
+/// of the project.
+#[rocket::async_trait]
+pub trait ViewRepository: Send + Sync {
+    /// Views visible to `user_id`, only those of one project with `project_id`.
+    async fn list(
+        &self,
+        user_id: i64,
+        project_id: Option<i64>,
+    ) -> Result<Vec<SavedView>, RepoError>;
+    async fn find(&self, id: i64, user_id: i64) -> Result<SavedView, RepoError>;
+    async fn create(&self, user_id: i64, view: &NewSavedView) -> Result<SavedView, RepoError>;
+    async fn update(
+        &self,
+        id: i64,
+        user_id: i64,
+        view: &NewSavedView,
+    ) -> Result<SavedView, RepoError>;
+    async fn delete(&self, id: i64) -> Result<(), RepoError>;
+}
+
+#[rocket::async_trait]
+pub trait SearchRepository: Send + Sync {
+    /// Every live project, task and comment, to rebuild an external index.
+    async fn documents(&self) -> Result<Vec<SearchDocument>, RepoError>;
+}
+
+pub type UserRepo = Arc<dyn UserRepository>;
+pub type ProjectRepo = Arc<dyn ProjectRepository>;
+pub type TaskRepo = Arc<dyn TaskRepository>;
+pub type CommentRepo = Arc<dyn CommentRepository>;
+pub type AttachmentRepo = Arc<dyn AttachmentRepository>;
+pub type ChecklistRepo = Arc<dyn ChecklistRepository>;
+pub type TimeEntryRepo = Arc<dyn TimeEntryRepository>;
+pub type LabelRepo = Arc<dyn LabelRepository>;
+pub type DependencyRepo = Arc<dyn DependencyRepository>;
+pub type ActivityRepo = Arc<dyn ActivityRepository>;
+pub type TrashRepo = Arc<dyn TrashRepository>;
+pub type ViewRepo = Arc<dyn ViewRepository>;
+pub type SearchRepo = Arc<dyn SearchRepository>;
+
+/// The storage handlers depend on, each part is managed as its own state.
+pub struct Repositories {
+    pub users: UserRepo,
+    pub projects: ProjectRepo,
+    pub tasks: TaskRepo,
+    pub comments: CommentRepo,
+    pub attachments: AttachmentRepo,
+    pub checklists: ChecklistRepo,
+    pub time_entries: TimeEntryRepo,
+    pub labels: LabelRepo,
+    pub dependencies: DependencyRepo,
+    pub activity: ActivityRepo,
+    pub trash: TrashRepo,
+    pub views: ViewRepo,
+    pub search: SearchRepo,
+}
+
+impl Repositories {
+    /// Every repository served by the same store.
+    fn from_store<S>(store: S) -> Self
+    where
+        S: UserRepository
+            + ProjectRepository
+            + TaskRepository
+            + CommentRepository
+            + AttachmentRepository
+            + ChecklistRepository
+            + TimeEntryRepository
+            + LabelRepository
+            + DependencyRepository
+            + ActivityRepository
+            + TrashRepository
+            + ViewRepository
+            + SearchRepository
+            + 'static,
+    {
+        let store = Arc::new(store);
+        Repositories {
+            users: store.clone(),
+            projects: store.clone(),
+            tasks: store.clone(),
+            comments: store.clone(),
+            attachments: store.clone(),
+            checklists: store.clone(),
+            time_entries: store.clone(),
+            labels: store.clone(),
+            dependencies: store.clone(),
+            activity: store.clone(),
+            trash: store.clone(),
+            views: store.clone(),
+            search: store,
+        }
+    }
+
+    pub fn mysql(db_pool: &DB, search: &SearchBackend, storage: &FileStorage) -> Self {
+        Self::from_store(mysql::Store::new(
+            db_pool.clone(),
+            search.clone(),
+            storage.clone(),
+        ))
+    }
+
+    pub fn sqlite(db_pool: &SqliteDB, search: &SearchBackend, storage: &FileStorage) -> Self {
+        Self::from_store(sqlite::Store::new(
+            db_pool.clone(),
+            search.clone(),
+            storage.clone(),
+        ))
+    }
+
+    #[cfg(feature = "postgres")]
+    pub fn postgres(
+        db_pool: &crate::db::PgDB,
+        search: &SearchBackend,
+        storage: &FileStorage,
+    ) -> Self {
+        Self::from_store(postgres::Store::new(
+            db_pool.clone(),
+            search.clone(),
+            storage.clone(),
+        ))
+    }
+}
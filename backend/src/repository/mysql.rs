@@ -0,0 +1,49 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, Utc};
+use rocket::time::PrimitiveDateTime;
+use sqlx::{mysql::MySqlArguments, query::Query, Executor, MySql};
+
+use crate::{db::DB, utils::to_primitive_date};
+
+use super::RepoError;
+
+// Every backend compiles its own copy of the shared implementation. It binds
+// `Time` by reference since it is only `Copy` on some backends.
+#[allow(clippy::duplicate_mod, clippy::needless_borrows_for_generic_args)]
+#[path = "sql/mod.rs"]
+mod sql;
+
+pub use self::sql::Store;
+
+type Db = MySql;
+type Pool = DB;
+/// How dates are bound, `DATETIME` columns hold UTC.
+type Time = PrimitiveDateTime;
+
+/// The queries are written in this dialect already.
+fn translate(query: &str) -> Cow<'_, str> {
+    Cow::Borrowed(query)
+}
+
+fn translate_insert(query: &str) -> Cow<'_, str> {
+    Cow::Borrowed(query)
+}
+
+/// Runs an insert built from `translate_insert` and returns the new id.
+async fn insert_id<'c, 'q>(
+    executor: impl Executor<'c, Database = Db>,
+    query: Query<'q, Db, MySqlArguments>,
+) -> Result<i64, sqlx::Error> {
+    Ok(query.execute(executor).await?.last_insert_id() as i64)
+}
+
+fn time(date: DateTime<Utc>) -> Result<Time, RepoError> {
+    to_primitive_date(date)
+        .map_err(|_| RepoError::Database(sqlx::Error::Encode("date out of range".into())))
+}
+
+/// `SUM` of an integer column, MySQL would return a `DECIMAL`.
+fn int_sum(column: &str) -> String {
+    format!("CAST(COALESCE(SUM({}), 0) AS SIGNED)", column)
+}
@@ -0,0 +1,70 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, Utc};
+use rocket::time::PrimitiveDateTime;
+use sqlx::{postgres::PgArguments, query::Query, Executor, Postgres, Row};
+
+use crate::{db::PgDB, utils::to_primitive_date};
+
+use super::RepoError;
+
+// Every backend compiles its own copy of the shared implementation. It binds
+// `Time` by reference since it is only `Copy` on some backends.
+#[allow(clippy::duplicate_mod, clippy::needless_borrows_for_generic_args)]
+#[path = "sql/mod.rs"]
+mod sql;
+
+pub use self::sql::Store;
+
+type Db = Postgres;
+type Pool = PgDB;
+/// How dates are bound, `TIMESTAMP` columns hold UTC.
+type Time = PrimitiveDateTime;
+
+/// Numbers the `?` placeholders and turns `INSERT IGNORE` into
+/// `ON CONFLICT DO NOTHING`. Question marks inside string literals are left alone.
+fn translate(query: &str) -> Cow<'_, str> {
+    let mut translated = String::with_capacity(query.len() + 16);
+    let mut placeholders = 0;
+    let mut in_string = false;
+    for c in query.chars() {
+        match c {
+            '\'' => {
+                in_string = !in_string;
+                translated.push(c);
+            }
+            '?' if !in_string => {
+                placeholders += 1;
+                translated.push('$');
+                translated.push_str(&placeholders.to_string());
+            }
+            _ => translated.push(c),
+        }
+    }
+    if let Some(rest) = translated.strip_prefix("INSERT IGNORE") {
+        translated = format!("INSERT{} ON CONFLICT DO NOTHING", rest);
+    }
+    Cow::Owned(translated)
+}
+
+fn translate_insert(query: &str) -> Cow<'_, str> {
+    Cow::Owned(format!("{} RETURNING id", translate(query)))
+}
+
+/// Runs an insert built from `translate_insert` and returns the new id.
+async fn insert_id<'c, 'q>(
+    executor: impl Executor<'c, Database = Db>,
+    query: Query<'q, Db, PgArguments>,
+) -> Result<i64, sqlx::Error> {
+    query.fetch_one(executor).await?.try_get(0)
+}
+
+fn time(date: DateTime<Utc>) -> Result<Time, RepoError> {
+    to_primitive_date(date)
+        .map_err(|_| RepoError::Database(sqlx::Error::Encode("date out of range".into())))
+}
+
+/// `SUM` of an integer column, Postgres would return a `NUMERIC`.
+fn int_sum(column: &str) -> String {
+    format!("COALESCE(SUM({}), 0)::BIGINT", column)
+}
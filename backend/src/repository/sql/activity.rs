@@ -0,0 +1,103 @@
+use crate::{
+    models::{
+        activity::{Activity, ActivityPage},
+        pagination::Pagination,
+    },
+    repository::{ActivityRepository, RepoError},
+};
+
+use super::{date, get, int, translate, DbRow, Store};
+
+fn activity_from_row(row: &DbRow) -> Result<Activity, RepoError> {
+    Ok(Activity {
+        id: int(row, "id")?,
+        task_id: int(row, "task_id")?,
+        project_id: int(row, "project_id")?,
+        user_id: int(row, "user_id")?,
+        username: get(row, "username")?,
+        action: get(row, "action")?,
+        field: get(row, "field")?,
+        old_value: get(row, "old_value")?,
+        new_value: get(row, "new_value")?,
+        created_at: date(row, "created_at")?,
+    })
+}
+
+#[rocket::async_trait]
+impl ActivityRepository for Store {
+    async fn task_history(
+        &self,
+        task_id: i64,
+        pagination: &Pagination,
+    ) -> Result<ActivityPage, RepoError> {
+        let total = sqlx::query_scalar(&translate(
+            "SELECT COUNT(*) FROM task_activity WHERE task_id = ?",
+        ))
+        .bind(task_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        let rows = sqlx::query(&translate(
+            "SELECT a.id, a.task_id, a.project_id, a.user_id, u.username, a.action, a.field,
+                a.old_value, a.new_value, a.created_at
+             FROM task_activity a JOIN users u ON u.id = a.user_id
+             WHERE a.task_id = ?
+             ORDER BY a.id DESC LIMIT ? OFFSET ?",
+        ))
+        .bind(task_id)
+        .bind(pagination.size)
+        .bind(pagination.offset())
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(ActivityPage {
+            page: pagination.page,
+            size: pagination.size,
+            total,
+            data: rows
+                .iter()
+                .map(activity_from_row)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    async fn project_activity(
+        &self,
+        project_id: i64,
+        pagination: &Pagination,
+    ) -> Result<ActivityPage, RepoError> {
+        let moved_out = project_id.to_string();
+        let total = sqlx::query_scalar(&translate(
+            "SELECT COUNT(*) FROM task_activity
+             WHERE project_id = ? OR (field = 'project_id' AND old_value = ?)",
+        ))
+        .bind(project_id)
+        .bind(&moved_out)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        let rows = sqlx::query(&translate(
+            "SELECT a.id, a.task_id, a.project_id, a.user_id, u.username, a.action, a.field,
+                a.old_value, a.new_value, a.created_at
+             FROM task_activity a JOIN users u ON u.id = a.user_id
+             WHERE a.project_id = ? OR (a.field = 'project_id' AND a.old_value = ?)
+             ORDER BY a.id DESC LIMIT ? OFFSET ?",
+        ))
+        .bind(project_id)
+        .bind(&moved_out)
+        .bind(pagination.size)
+        .bind(pagination.offset())
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(ActivityPage {
+            page: pagination.page,
+            size: pagination.size,
+            total,
+            data: rows
+                .iter()
+                .map(activity_from_row)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
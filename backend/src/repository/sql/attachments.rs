@@ -0,0 +1,165 @@
+use crate::{
+    models::attachment::Attachment,
+    repository::{AttachmentRepository, RepoError},
+};
+
+use super::{date, get, insert_id, int, translate, translate_insert, Connection, DbRow, Store};
+
+fn attachment_from_row(row: &DbRow) -> Result<Attachment, RepoError> {
+    Ok(Attachment {
+        id: int(row, "id")?,
+        task_id: int(row, "task_id")?,
+        user_id: int(row, "user_id")?,
+        filename: get(row, "filename")?,
+        content_type: get(row, "content_type")?,
+        size: int(row, "size")?,
+        sha256: get(row, "sha256")?,
+        created_at: date(row, "created_at")?,
+    })
+}
+
+/// Locks the `attachment_blobs` row of `sha256` until the end of the transaction,
+/// creating it if needed, so uploads and deletions of the same content run one
+/// after the other and the content never goes away under a new attachment.
+async fn lock_content(conn: &mut Connection, sha256: &str) -> Result<(), RepoError> {
+    let lock = translate("UPDATE attachment_blobs SET sha256 = sha256 WHERE sha256 = ?");
+    let locked = sqlx::query(&lock).bind(sha256).execute(&mut *conn).await?;
+    if locked.rows_affected() > 0 {
+        return Ok(());
+    }
+    let created = sqlx::query(&translate(
+        "INSERT IGNORE INTO attachment_blobs (sha256) VALUES (?)",
+    ))
+    .bind(sha256)
+    .execute(&mut *conn)
+    .await?;
+    if created.rows_affected() == 0 {
+        // Created by a concurrent upload in the meantime
+        sqlx::query(&lock).bind(sha256).execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+impl Store {
+    /// Deletes the content once no attachment refers to it, called after the
+    /// attachments are gone for good. The content is locked and its references
+    /// counted again, so an upload committed in the meantime keeps it, and the
+    /// blob is deleted before the row, a failed commit only leaves a row behind.
+    async fn drop_unreferenced(&self, sha256: &str) -> Result<(), RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        lock_content(&mut tx, sha256).await?;
+        let references: i64 = sqlx::query_scalar(&translate(
+            "SELECT COUNT(*) FROM task_attachments WHERE sha256 = ?",
+        ))
+        .bind(sha256)
+        .fetch_one(&mut *tx)
+        .await?;
+        if references > 0 {
+            return Ok(());
+        }
+        self.storage
+            .delete(sha256)
+            .await
+            .map_err(RepoError::Storage)?;
+        sqlx::query(&translate("DELETE FROM attachment_blobs WHERE sha256 = ?"))
+            .bind(sha256)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[rocket::async_trait]
+impl AttachmentRepository for Store {
+    async fn list(&self, task_id: i64) -> Result<Vec<Attachment>, RepoError> {
+        let rows = sqlx::query(&translate(
+            "SELECT id, task_id, user_id, filename, content_type, size, sha256, created_at
+             FROM task_attachments WHERE task_id = ? ORDER BY created_at, id",
+        ))
+        .bind(task_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+        rows.iter().map(attachment_from_row).collect()
+    }
+
+    async fn create(
+        &self,
+        attachment: Attachment,
+        content: &[u8],
+    ) -> Result<Attachment, RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        lock_content(&mut tx, &attachment.sha256).await?;
+        // Identical files are stored once and shared by every attachment row
+        let stored = self
+            .storage
+            .exists(&attachment.sha256)
+            .await
+            .map_err(RepoError::Storage)?;
+        if !stored {
+            self.storage
+                .put(&attachment.sha256, content)
+                .await
+                .map_err(RepoError::Storage)?;
+        }
+
+        let id = insert_id(
+            &mut *tx,
+            sqlx::query(&translate_insert(
+                "INSERT INTO task_attachments (task_id, user_id, filename, content_type, size, sha256)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            ))
+            .bind(attachment.task_id)
+            .bind(attachment.user_id)
+            .bind(&attachment.filename)
+            .bind(&attachment.content_type)
+            .bind(attachment.size)
+            .bind(&attachment.sha256),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(Attachment { id, ..attachment })
+    }
+
+    async fn find(&self, id: i64, task_id: i64) -> Result<Attachment, RepoError> {
+        let row = sqlx::query(&translate(
+            "SELECT id, task_id, user_id, filename, content_type, size, sha256, created_at
+             FROM task_attachments WHERE id = ? AND task_id = ?",
+        ))
+        .bind(id)
+        .bind(task_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        attachment_from_row(&row)
+    }
+
+    async fn delete(&self, id: i64, task_id: i64) -> Result<(), RepoError> {
+        let sha256: String = sqlx::query_scalar(&translate(
+            "SELECT sha256 FROM task_attachments WHERE id = ? AND task_id = ?",
+        ))
+        .bind(id)
+        .bind(task_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        let deleted = sqlx::query(&translate(
+            "DELETE FROM task_attachments WHERE id = ? AND task_id = ?",
+        ))
+        .bind(id)
+        .bind(task_id)
+        .execute(&self.db_pool)
+        .await?;
+        if deleted.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+        // The attachment is gone either way, content left behind only takes space
+        if let Err(e) = self.drop_unreferenced(&sha256).await {
+            tracing::warn!(error = ?e, sha256, "failed to delete attachment content");
+        }
+        Ok(())
+    }
+
+    async fn release(&self, sha256: &str) -> Result<(), RepoError> {
+        self.drop_unreferenced(sha256).await
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+
+use crate::{
+    models::{
+        activity::{diff, ActivityAction, FieldChange},
+        bulk::BulkOperation,
+        tasks::TaskStatus,
+    },
+    repository::RepoError,
+};
+
+use super::{
+    end_of_column, move_descendants, open_blockers, project_archived, record_activity, select_task,
+    spawn_next_occurrence, subtree_ids, translate, trash_task, Connection, Deletion,
+};
+
+/// Applies the operation to one task, following the same rules as the single task
+/// endpoints. Deleted subtasks are added to `trashed`, every task changed along the
+/// way (subtasks, new occurrences) to `touched`.
+pub(super) async fn apply_operation(
+    conn: &mut Connection,
+    user_id: i64,
+    task_id: i64,
+    operation: &BulkOperation,
+    deletion: Option<&Deletion>,
+    trashed: &mut HashSet<i64>,
+    touched: &mut Vec<i64>,
+) -> Result<(), RepoError> {
+    let task = select_task(&mut *conn, task_id, user_id).await?;
+    if project_archived(&mut *conn, task.project_id, user_id).await? {
+        return Err(RepoError::Conflict(
+            "project_archived",
+            "Project is archived and read-only",
+        ));
+    }
+
+    match operation {
+        BulkOperation::MoveProject { project_id } => {
+            let project_id = *project_id;
+            if project_id == task.project_id {
+                return Ok(());
+            }
+            // Same as changing the project in `update_task`: detached, subtree included
+            let rank = end_of_column(&mut *conn, project_id, task.status).await?;
+            sqlx::query(&translate(
+                "UPDATE tasks SET project_id = ?, parent_id = NULL, board_rank = ? WHERE id = ?",
+            ))
+            .bind(project_id)
+            .bind(&rank)
+            .bind(task_id)
+            .execute(&mut *conn)
+            .await?;
+
+            let project_change = diff("project_id", Some(&task.project_id), Some(&project_id));
+            let changes: Vec<FieldChange> = [
+                project_change,
+                diff("parent_id", task.parent_id.as_ref(), None),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            record_activity(
+                &mut *conn,
+                task_id,
+                project_id,
+                user_id,
+                ActivityAction::Updated,
+                &changes,
+            )
+            .await?;
+
+            let project_change = diff("project_id", Some(&task.project_id), Some(&project_id));
+            let descendants = subtree_ids(&mut *conn, task_id).await?;
+            move_descendants(
+                &mut *conn,
+                &descendants,
+                project_id,
+                user_id,
+                project_change.as_slice(),
+            )
+            .await?;
+            touched.extend(descendants);
+        }
+        BulkOperation::SetStatus { status } => {
+            if *status == task.status {
+                return Ok(());
+            }
+            if *status == TaskStatus::Done && open_blockers(&mut *conn, task_id).await? > 0 {
+                return Err(RepoError::Conflict(
+                    "task_blocked",
+                    "Task is blocked by unfinished dependencies",
+                ));
+            }
+            let rank = end_of_column(&mut *conn, task.project_id, *status).await?;
+            sqlx::query(&translate(
+                "UPDATE tasks SET status = ?, board_rank = ? WHERE id = ?",
+            ))
+            .bind(status.as_str())
+            .bind(&rank)
+            .bind(task_id)
+            .execute(&mut *conn)
+            .await?;
+            let change = diff(
+                "status",
+                Some(&task.status.as_str()),
+                Some(&status.as_str()),
+            );
+            record_activity(
+                &mut *conn,
+                task_id,
+                task.project_id,
+                user_id,
+                ActivityAction::Updated,
+                change.as_slice(),
+            )
+            .await?;
+            if *status == TaskStatus::Done {
+                let next = spawn_next_occurrence(&mut *conn, task_id, None).await?;
+                touched.extend(next);
+            }
+        }
+        BulkOperation::SetPriority { priority } => {
+            if *priority == task.priority {
+                return Ok(());
+            }
+            sqlx::query(&translate("UPDATE tasks SET priority = ? WHERE id = ?"))
+                .bind(priority.as_str())
+                .bind(task_id)
+                .execute(&mut *conn)
+                .await?;
+            let change = diff(
+                "priority",
+                Some(&task.priority.as_str()),
+                Some(&priority.as_str()),
+            );
+            record_activity(
+                &mut *conn,
+                task_id,
+                task.project_id,
+                user_id,
+                ActivityAction::Updated,
+                change.as_slice(),
+            )
+            .await?;
+        }
+        BulkOperation::AddLabel { label } => {
+            let result = sqlx::query(&translate(
+                "INSERT IGNORE INTO task_labels (task_id, label) VALUES (?, ?)",
+            ))
+            .bind(task_id)
+            .bind(label)
+            .execute(&mut *conn)
+            .await?;
+            if result.rows_affected() > 0 {
+                let change = diff("label", None, Some(label));
+                record_activity(
+                    &mut *conn,
+                    task_id,
+                    task.project_id,
+                    user_id,
+                    ActivityAction::Updated,
+                    change.as_slice(),
+                )
+                .await?;
+            }
+        }
+        BulkOperation::Delete => {
+            // Subtasks go to the trash with their parent, like `?subtasks=delete`
+            let mut removed = subtree_ids(&mut *conn, task_id).await?;
+            removed.push(task_id);
+            let deletion = deletion.expect("deletes run within a deletion");
+            for id in removed {
+                trash_task(&mut *conn, id, deletion).await?;
+                record_activity(
+                    &mut *conn,
+                    id,
+                    task.project_id,
+                    user_id,
+                    ActivityAction::Deleted,
+                    &[],
+                )
+                .await?;
+                trashed.insert(id);
+                touched.push(id);
+            }
+        }
+    }
+    Ok(())
+}
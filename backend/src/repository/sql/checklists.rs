@@ -0,0 +1,129 @@
+use crate::{
+    models::checklist::ChecklistItem,
+    repository::{ChecklistRepository, RepoError},
+};
+
+use super::{get, insert_id, int, translate, translate_insert, DbRow, Store};
+
+fn item_from_row(row: &DbRow) -> Result<ChecklistItem, RepoError> {
+    Ok(ChecklistItem {
+        id: int(row, "id")?,
+        task_id: int(row, "task_id")?,
+        text: get(row, "text")?,
+        checked: get(row, "checked")?,
+        position: int(row, "position")?,
+    })
+}
+
+#[rocket::async_trait]
+impl ChecklistRepository for Store {
+    async fn list(&self, task_id: i64) -> Result<Vec<ChecklistItem>, RepoError> {
+        let rows = sqlx::query(&translate(
+            "SELECT id, task_id, text, checked, position FROM checklist_items
+             WHERE task_id = ? ORDER BY position, id",
+        ))
+        .bind(task_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+        rows.iter().map(item_from_row).collect()
+    }
+
+    async fn add(&self, task_id: i64, text: &str) -> Result<ChecklistItem, RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        // New items go to the end of the list
+        let row = sqlx::query(&translate(
+            "SELECT COALESCE(MAX(position) + 1, 0) AS position FROM checklist_items WHERE task_id = ?",
+        ))
+        .bind(task_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let position = int(&row, "position")?;
+        let id = insert_id(
+            &mut *tx,
+            sqlx::query(&translate_insert(
+                "INSERT INTO checklist_items (task_id, text, position) VALUES (?, ?, ?)",
+            ))
+            .bind(task_id)
+            .bind(text)
+            .bind(position),
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(ChecklistItem {
+            id,
+            task_id,
+            text: text.to_string(),
+            checked: false,
+            position,
+        })
+    }
+
+    async fn reorder(&self, task_id: i64, ids: &[i64]) -> Result<Vec<ChecklistItem>, RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        let mut existing: Vec<i64> = sqlx::query_scalar(&translate(
+            "SELECT id FROM checklist_items WHERE task_id = ? FOR UPDATE",
+        ))
+        .bind(task_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut requested = ids.to_vec();
+        existing.sort_unstable();
+        requested.sort_unstable();
+        if existing != requested {
+            return Err(RepoError::Invalid(
+                "incomplete_order",
+                "The order must list every checklist item exactly once",
+            ));
+        }
+
+        for (position, id) in ids.iter().enumerate() {
+            sqlx::query(&translate(
+                "UPDATE checklist_items SET position = ? WHERE id = ?",
+            ))
+            .bind(position as i64)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.list(task_id).await
+    }
+
+    async fn toggle(&self, id: i64, task_id: i64) -> Result<ChecklistItem, RepoError> {
+        let result = sqlx::query(&translate(
+            "UPDATE checklist_items SET checked = NOT checked WHERE id = ? AND task_id = ?",
+        ))
+        .bind(id)
+        .bind(task_id)
+        .execute(&self.db_pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+
+        let row = sqlx::query(&translate(
+            "SELECT id, task_id, text, checked, position FROM checklist_items WHERE id = ?",
+        ))
+        .bind(id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        item_from_row(&row)
+    }
+
+    async fn delete(&self, id: i64, task_id: i64) -> Result<(), RepoError> {
+        let result = sqlx::query(&translate(
+            "DELETE FROM checklist_items WHERE id = ? AND task_id = ?",
+        ))
+        .bind(id)
+        .bind(task_id)
+        .execute(&self.db_pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+}
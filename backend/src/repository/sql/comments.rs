@@ -0,0 +1,153 @@
+use chrono::Utc;
+
+use crate::{
+    models::comment::{Comment, NewComment, MAX_REPLY_DEPTH},
+    repository::{CommentRepository, RepoError},
+};
+
+use super::{
+    date, get, insert_id, int, opt_date, opt_int, sync_comment, time, translate, translate_insert,
+    DbRow, Pool, Store,
+};
+
+const COMMENT_COLUMNS: &str =
+    "c.id, c.task_id, c.user_id, u.username, c.parent_id, c.body, c.created_at,
+    c.edited_at, c.deleted_at";
+
+fn comment_from_row(row: &DbRow) -> Result<Comment, RepoError> {
+    let deleted = opt_date(row, "deleted_at")?.is_some();
+    Ok(Comment {
+        id: int(row, "id")?,
+        task_id: int(row, "task_id")?,
+        user_id: int(row, "user_id")?,
+        author: get(row, "username")?,
+        parent_id: opt_int(row, "parent_id")?,
+        body: if deleted {
+            None
+        } else {
+            Some(get(row, "body")?)
+        },
+        deleted,
+        created_at: date(row, "created_at")?,
+        edited_at: opt_date(row, "edited_at")?,
+        replies: Vec::new(),
+    })
+}
+
+async fn select_comment(db_pool: &Pool, id: i64) -> Result<Comment, RepoError> {
+    let row = sqlx::query(&translate(&format!(
+        "SELECT {} FROM task_comments c JOIN users u ON u.id = c.user_id WHERE c.id = ?",
+        COMMENT_COLUMNS
+    )))
+    .bind(id)
+    .fetch_one(db_pool)
+    .await?;
+    comment_from_row(&row)
+}
+
+#[rocket::async_trait]
+impl CommentRepository for Store {
+    async fn list(&self, task_id: i64) -> Result<Vec<Comment>, RepoError> {
+        let rows = sqlx::query(&translate(&format!(
+            "SELECT {} FROM task_comments c JOIN users u ON u.id = c.user_id
+             WHERE c.task_id = ? ORDER BY c.created_at, c.id",
+            COMMENT_COLUMNS
+        )))
+        .bind(task_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+        rows.iter().map(comment_from_row).collect()
+    }
+
+    async fn create(
+        &self,
+        task_id: i64,
+        user_id: i64,
+        comment: &NewComment,
+    ) -> Result<Comment, RepoError> {
+        // Replies stay within the task and cannot target a deleted comment
+        if let Some(parent_id) = comment.parent_id {
+            let mut ancestor = sqlx::query_scalar::<_, Option<i64>>(&translate(
+                "SELECT parent_id FROM task_comments WHERE id = ? AND task_id = ? AND deleted_at IS NULL",
+            ))
+            .bind(parent_id)
+            .bind(task_id)
+            .fetch_one(&self.db_pool)
+            .await?;
+            // The parent is one level up, each of its ancestors one more
+            let mut depth = 1;
+            while let Some(id) = ancestor {
+                depth += 1;
+                if depth > MAX_REPLY_DEPTH {
+                    return Err(RepoError::Invalid(
+                        "thread_too_deep",
+                        "Replies cannot nest more than 10 levels deep",
+                    ));
+                }
+                ancestor = sqlx::query_scalar(&translate(
+                    "SELECT parent_id FROM task_comments WHERE id = ?",
+                ))
+                .bind(id)
+                .fetch_one(&self.db_pool)
+                .await?;
+            }
+        }
+
+        let id = insert_id(
+            &self.db_pool,
+            sqlx::query(&translate_insert(
+                "INSERT INTO task_comments (task_id, user_id, parent_id, body) VALUES (?, ?, ?, ?)",
+            ))
+            .bind(task_id)
+            .bind(user_id)
+            .bind(comment.parent_id)
+            .bind(&comment.body),
+        )
+        .await?;
+        sync_comment(&self.db_pool, &self.search, id).await;
+        select_comment(&self.db_pool, id).await
+    }
+
+    async fn edit(
+        &self,
+        id: i64,
+        task_id: i64,
+        user_id: i64,
+        body: &str,
+    ) -> Result<Comment, RepoError> {
+        let result = sqlx::query(&translate(
+            "UPDATE task_comments SET body = ?, edited_at = ?
+             WHERE id = ? AND task_id = ? AND user_id = ? AND deleted_at IS NULL",
+        ))
+        .bind(body)
+        .bind(time(Utc::now())?)
+        .bind(id)
+        .bind(task_id)
+        .bind(user_id)
+        .execute(&self.db_pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+        sync_comment(&self.db_pool, &self.search, id).await;
+        select_comment(&self.db_pool, id).await
+    }
+
+    async fn delete(&self, id: i64, task_id: i64, user_id: i64) -> Result<(), RepoError> {
+        let result = sqlx::query(&translate(
+            "UPDATE task_comments SET deleted_at = ?
+             WHERE id = ? AND task_id = ? AND user_id = ? AND deleted_at IS NULL",
+        ))
+        .bind(time(Utc::now())?)
+        .bind(id)
+        .bind(task_id)
+        .bind(user_id)
+        .execute(&self.db_pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+        sync_comment(&self.db_pool, &self.search, id).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    models::tasks::{TaskDependencies, TaskSummary},
+    repository::{DependencyRepository, RepoError},
+};
+
+use super::{get, int, translate, Connection, Pool, Store};
+
+/// Live tasks at the `other` end of the edges whose `column` is `task_id`.
+async fn linked_tasks(
+    db_pool: &Pool,
+    task_id: i64,
+    column: &str,
+    other: &str,
+) -> Result<Vec<TaskSummary>, RepoError> {
+    let rows = sqlx::query(&translate(&format!(
+        "SELECT t.id, t.title, t.status FROM task_dependencies d
+         JOIN tasks t ON t.id = d.{} WHERE d.{} = ? AND t.deleted_at IS NULL",
+        other, column
+    )))
+    .bind(task_id)
+    .fetch_all(db_pool)
+    .await?;
+    rows.iter()
+        .map(|row| {
+            Ok(TaskSummary {
+                id: int(row, "id")?,
+                title: get(row, "title")?,
+                status: get::<String>(row, "status")?.parse().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Tasks `task_id` depends on, directly or transitively.
+async fn upstream(conn: &mut Connection, task_id: i64) -> Result<BTreeSet<i64>, RepoError> {
+    let ids: Vec<i64> = sqlx::query_scalar(&translate(
+        "WITH RECURSIVE upstream (id) AS (
+            SELECT depends_on_id FROM task_dependencies WHERE task_id = ?
+            UNION
+            SELECT d.depends_on_id FROM task_dependencies d JOIN upstream u ON d.task_id = u.id
+         )
+         SELECT id FROM upstream",
+    ))
+    .bind(task_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(ids.into_iter().collect())
+}
+
+#[rocket::async_trait]
+impl DependencyRepository for Store {
+    async fn list(&self, task_id: i64) -> Result<TaskDependencies, RepoError> {
+        Ok(TaskDependencies {
+            blocked_by: linked_tasks(&self.db_pool, task_id, "task_id", "depends_on_id").await?,
+            blocks: linked_tasks(&self.db_pool, task_id, "depends_on_id", "task_id").await?,
+        })
+    }
+
+    async fn add(&self, task_id: i64, depends_on: i64) -> Result<(), RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+
+        // Lock both tasks and everything the new dependency depends on, in id order,
+        // until no dependency was added in between. An edge that could close a cycle
+        // starts at one of them, so concurrent additions wait for this one
+        let mut locked = BTreeSet::new();
+        let upstream = loop {
+            let upstream = upstream(&mut tx, depends_on).await?;
+            let involved: BTreeSet<i64> = upstream
+                .iter()
+                .copied()
+                .chain([task_id, depends_on])
+                .collect();
+            if involved.is_subset(&locked) {
+                break upstream;
+            }
+            for id in involved.difference(&locked) {
+                sqlx::query(&translate("SELECT id FROM tasks WHERE id = ? FOR UPDATE"))
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            locked.extend(involved);
+        };
+
+        // Adding the edge closes a cycle if the new dependency already (transitively) depends on this task
+        if upstream.contains(&task_id) {
+            return Err(RepoError::Conflict(
+                "dependency_cycle",
+                "Dependency would create a cycle",
+            ));
+        }
+
+        sqlx::query(&translate(
+            "INSERT IGNORE INTO task_dependencies (task_id, depends_on_id) VALUES (?, ?)",
+        ))
+        .bind(task_id)
+        .bind(depends_on)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn remove(&self, task_id: i64, depends_on: i64) -> Result<(), RepoError> {
+        let result = sqlx::query(&translate(
+            "DELETE FROM task_dependencies WHERE task_id = ? AND depends_on_id = ?",
+        ))
+        .bind(task_id)
+        .bind(depends_on)
+        .execute(&self.db_pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{
+    models::{
+        activity::{diff, ActivityAction},
+        tasks::Tasks,
+    },
+    repository::{LabelRepository, RepoError},
+};
+
+use super::{record_activity, translate, Store};
+
+#[rocket::async_trait]
+impl LabelRepository for Store {
+    async fn list(&self, task_id: i64) -> Result<Vec<String>, RepoError> {
+        let labels = sqlx::query_scalar(&translate(
+            "SELECT label FROM task_labels WHERE task_id = ? ORDER BY label",
+        ))
+        .bind(task_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+        Ok(labels)
+    }
+
+    async fn add(&self, task: &Tasks, user_id: i64, label: &str) -> Result<Vec<String>, RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        let result = sqlx::query(&translate(
+            "INSERT IGNORE INTO task_labels (task_id, label) VALUES (?, ?)",
+        ))
+        .bind(task.id)
+        .bind(label)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() > 0 {
+            let change = diff("label", None, Some(&label));
+            record_activity(
+                &mut tx,
+                task.id,
+                task.project_id,
+                user_id,
+                ActivityAction::Updated,
+                change.as_slice(),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.list(task.id).await
+    }
+
+    async fn remove(&self, task: &Tasks, user_id: i64, label: &str) -> Result<(), RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        let result = sqlx::query(&translate(
+            "DELETE FROM task_labels WHERE task_id = ? AND label = ?",
+        ))
+        .bind(task.id)
+        .bind(label)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+        let change = diff("label", Some(&label), None);
+        record_activity(
+            &mut tx,
+            task.id,
+            task.project_id,
+            user_id,
+            ActivityAction::Updated,
+            change.as_slice(),
+        )
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
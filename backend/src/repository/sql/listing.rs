@@ -0,0 +1,129 @@
+use chrono::Utc;
+use sqlx::query::Query;
+
+use crate::{
+    models::{
+        bulk::TaskFilter,
+        pagination::Pagination,
+        tasks::{ListOptions, TaskPage, TaskSort},
+    },
+    repository::RepoError,
+    utils::filter::{Filter, SqlParam, PRIORITY_RANK},
+};
+
+use super::{int, task_from_row, time, translate, Db, Pool, TASK_COLUMNS};
+
+type DbQuery<'q> = Query<'q, Db, <Db as sqlx::Database>::Arguments<'q>>;
+
+fn bind_params<'q>(
+    mut query: DbQuery<'q>,
+    params: &'q [SqlParam],
+) -> Result<DbQuery<'q>, RepoError> {
+    for param in params {
+        query = match param {
+            SqlParam::Int(value) => query.bind(*value),
+            SqlParam::Text(value) => query.bind(value.as_str()),
+            SqlParam::DateTime(value) => query.bind(time(*value)?),
+        };
+    }
+    Ok(query)
+}
+
+fn order_by(sort: TaskSort, descending: bool) -> String {
+    let direction = if descending { "DESC" } else { "ASC" };
+    match sort {
+        TaskSort::Created => format!("t.created_at {0}, t.id {0}", direction),
+        TaskSort::DueDate => format!("t.due_date IS NULL, t.due_date {0}, t.id {0}", direction),
+        TaskSort::Priority => format!("{0} {1}, t.id {1}", PRIORITY_RANK, direction),
+        TaskSort::Title => format!("t.title {0}, t.id {0}", direction),
+        TaskSort::Rank => format!("t.project_id, t.board_rank {0}, t.id {0}", direction),
+    }
+}
+
+/// One page of the live tasks of `user_id` matching `filter`.
+pub(super) async fn query_tasks(
+    db_pool: &Pool,
+    user_id: i64,
+    filter: &Filter,
+    options: &ListOptions,
+    pagination: &Pagination,
+) -> Result<TaskPage, RepoError> {
+    let compiled = filter.to_sql(Utc::now());
+    let from = format!(
+        "FROM tasks t JOIN projects p ON p.id = t.project_id
+         WHERE t.user_id = ? AND t.deleted_at IS NULL AND p.deleted_at IS NULL
+            AND (? OR p.archived_at IS NULL) AND (? IS NULL OR t.project_id = ?) AND {}",
+        compiled.sql
+    );
+
+    let count_sql = translate(&format!("SELECT COUNT(*) AS total {}", from)).into_owned();
+    let count = sqlx::query(&count_sql)
+        .bind(user_id)
+        .bind(options.include_archived)
+        .bind(options.project_id)
+        .bind(options.project_id);
+    let row = bind_params(count, &compiled.params)?
+        .fetch_one(db_pool)
+        .await?;
+    let total = int(&row, "total")?;
+
+    let select_sql = translate(&format!(
+        "SELECT {} {} ORDER BY {} LIMIT ? OFFSET ?",
+        TASK_COLUMNS,
+        from,
+        order_by(options.sort, options.descending)
+    ))
+    .into_owned();
+    let select = sqlx::query(&select_sql)
+        .bind(user_id)
+        .bind(options.include_archived)
+        .bind(options.project_id)
+        .bind(options.project_id);
+    let rows = bind_params(select, &compiled.params)?
+        .bind(pagination.size)
+        .bind(pagination.offset())
+        .fetch_all(db_pool)
+        .await?;
+
+    Ok(TaskPage {
+        page: pagination.page,
+        size: pagination.size,
+        total,
+        data: rows.iter().map(task_from_row).collect::<Result<_, _>>()?,
+    })
+}
+
+/// Ids of the live tasks in unarchived projects matching `filter`, at most `limit`.
+pub(super) async fn select_tasks(
+    db_pool: &Pool,
+    user_id: i64,
+    filter: &TaskFilter,
+    limit: i64,
+) -> Result<Vec<i64>, RepoError> {
+    let status = filter.status.map(|status| status.as_str());
+    let priority = filter.priority.map(|priority| priority.as_str());
+    let ids = sqlx::query_scalar(&translate(
+        "SELECT t.id FROM tasks t JOIN projects p ON p.id = t.project_id
+         WHERE t.user_id = ? AND t.deleted_at IS NULL
+            AND p.deleted_at IS NULL AND p.archived_at IS NULL
+            AND (? IS NULL OR t.project_id = ?)
+            AND (? IS NULL OR t.status = ?)
+            AND (? IS NULL OR t.priority = ?)
+            AND (? IS NULL OR EXISTS (
+                SELECT 1 FROM task_labels l WHERE l.task_id = t.id AND l.label = ?))
+         ORDER BY t.id LIMIT ?",
+    ))
+    .bind(user_id)
+    .bind(filter.project_id)
+    .bind(filter.project_id)
+    .bind(status)
+    .bind(status)
+    .bind(priority)
+    .bind(priority)
+    .bind(filter.label.as_deref())
+    .bind(filter.label.as_deref())
+    .bind(limit)
+    .fetch_all(db_pool)
+    .await?;
+    Ok(ids)
+}
@@ -0,0 +1,425 @@
+//! The repositories of the SQL databases. The files of this directory are
+//! compiled once per backend, included with `#[path]` by `mysql.rs`, `sqlite.rs`
+//! and `postgres.rs`, so `super` is the backend being compiled. Queries are
+//! written in MySQL syntax with `?` placeholders and go through `translate`, dates
+//! are bound through `time` and read back as `PrimitiveDateTime` everywhere.
+
+use chrono::{DateTime, SubsecRound, Utc};
+use rocket::time::PrimitiveDateTime;
+use sqlx::{Decode, Executor, Row, Type};
+
+use crate::{
+    models::{
+        activity::{ActivityAction, FieldChange},
+        checklist::ChecklistProgress,
+        project::Project,
+        tasks::{TaskStatus, Tasks},
+    },
+    search::SearchBackend,
+    storage::FileStorage,
+    utils::{parse_date, rank::rank_between, rrule::RecurrenceRule},
+};
+
+use super::{insert_id, int_sum, time, translate, translate_insert, Db, Pool, RepoError, Time};
+
+mod activity;
+mod attachments;
+mod bulk;
+mod checklists;
+mod comments;
+mod dependencies;
+mod labels;
+mod listing;
+mod projects;
+mod search;
+mod tasks;
+mod time_entries;
+mod trash;
+mod users;
+mod views;
+
+use self::search::{sync_comment, sync_project, sync_tasks};
+
+type DbRow = <Db as sqlx::Database>::Row;
+type Connection = <Db as sqlx::Database>::Connection;
+
+/// Implements every repository on one pool. Writes keep an external search index
+/// in step once they are committed, attachment content is stored and deleted
+/// along with the rows referring to it.
+pub struct Store {
+    db_pool: Pool,
+    search: SearchBackend,
+    storage: FileStorage,
+}
+
+impl Store {
+    pub fn new(db_pool: Pool, search: SearchBackend, storage: FileStorage) -> Self {
+        Store {
+            db_pool,
+            search,
+            storage,
+        }
+    }
+}
+
+const TASK_COLUMNS: &str = "t.id, t.user_id, t.project_id, t.parent_id, t.title, t.description, t.status,
+    t.priority, t.due_date, t.recurrence, t.story_points, t.estimate_hours, t.board_rank, t.created_at,
+    (SELECT COUNT(*) FROM checklist_items c WHERE c.task_id = t.id AND c.checked) AS checklist_checked,
+    (SELECT COUNT(*) FROM checklist_items c WHERE c.task_id = t.id) AS checklist_total";
+
+fn get<'r, T>(row: &'r DbRow, name: &str) -> Result<T, RepoError>
+where
+    T: Decode<'r, Db> + Type<Db>,
+{
+    Ok(row.try_get(name)?)
+}
+
+/// An integer column, whatever its width.
+fn int(row: &DbRow, name: &str) -> Result<i64, RepoError> {
+    Ok(
+        opt_int(row, name)?.ok_or_else(|| sqlx::Error::ColumnDecode {
+            index: name.to_string(),
+            source: "unexpected NULL".into(),
+        })?,
+    )
+}
+
+fn opt_int(row: &DbRow, name: &str) -> Result<Option<i64>, RepoError> {
+    match row.try_get::<Option<i64>, _>(name) {
+        Ok(value) => Ok(value),
+        // Postgres `INTEGER` columns only decode as `i32`
+        Err(_) => Ok(row.try_get::<Option<i32>, _>(name)?.map(i64::from)),
+    }
+}
+
+fn utc(date: PrimitiveDateTime) -> Result<DateTime<Utc>, RepoError> {
+    parse_date(date)
+        .map_err(|_| RepoError::Database(sqlx::Error::Decode("date out of range".into())))
+}
+
+fn date(row: &DbRow, name: &str) -> Result<DateTime<Utc>, RepoError> {
+    utc(get(row, name)?)
+}
+
+fn opt_date(row: &DbRow, name: &str) -> Result<Option<DateTime<Utc>>, RepoError> {
+    get::<Option<PrimitiveDateTime>>(row, name)?
+        .map(utc)
+        .transpose()
+}
+
+fn task_from_row(row: &DbRow) -> Result<Tasks, RepoError> {
+    Ok(Tasks {
+        id: int(row, "id")?,
+        user_id: int(row, "user_id")?,
+        project_id: int(row, "project_id")?,
+        parent_id: opt_int(row, "parent_id")?,
+        title: get(row, "title")?,
+        description: get(row, "description")?,
+        status: get::<String>(row, "status")?.parse().unwrap_or_default(),
+        priority: get::<String>(row, "priority")?.parse().unwrap_or_default(),
+        due_date: opt_date(row, "due_date")?,
+        recurrence: get(row, "recurrence")?,
+        story_points: opt_int(row, "story_points")?,
+        estimate_hours: get(row, "estimate_hours")?,
+        rank: get(row, "board_rank")?,
+        checklist: ChecklistProgress {
+            checked: int(row, "checklist_checked")?,
+            total: int(row, "checklist_total")?,
+        },
+        created_at: date(row, "created_at")?,
+    })
+}
+
+fn project_from_row(row: &DbRow) -> Result<Project, RepoError> {
+    Ok(Project {
+        id: int(row, "id")?,
+        user_id: int(row, "user_id")?,
+        name: get(row, "name")?,
+        archived_at: opt_date(row, "archived_at")?,
+    })
+}
+
+/// Loads a single live task owned by `user_id`.
+async fn select_task(
+    executor: impl Executor<'_, Database = Db>,
+    id: i64,
+    user_id: i64,
+) -> Result<Tasks, RepoError> {
+    let row = sqlx::query(&translate(&format!(
+        "SELECT {} FROM tasks t WHERE t.id = ? AND t.user_id = ? AND t.deleted_at IS NULL",
+        TASK_COLUMNS
+    )))
+    .bind(id)
+    .bind(user_id)
+    .fetch_one(executor)
+    .await?;
+    task_from_row(&row)
+}
+
+/// Appends to the activity log of a task, one row per changed field. Meant to run
+/// on the transaction making the change so the log never disagrees with the data.
+async fn record_activity(
+    conn: &mut Connection,
+    task_id: i64,
+    project_id: i64,
+    user_id: i64,
+    action: ActivityAction,
+    changes: &[FieldChange],
+) -> Result<(), sqlx::Error> {
+    if changes.is_empty() {
+        if action == ActivityAction::Updated {
+            return Ok(());
+        }
+        sqlx::query(&translate(
+            "INSERT INTO task_activity (task_id, project_id, user_id, action) VALUES (?, ?, ?, ?)",
+        ))
+        .bind(task_id)
+        .bind(project_id)
+        .bind(user_id)
+        .bind(action.as_str())
+        .execute(&mut *conn)
+        .await?;
+        return Ok(());
+    }
+
+    for change in changes {
+        sqlx::query(&translate(
+            "INSERT INTO task_activity (task_id, project_id, user_id, action, field, old_value, new_value)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        ))
+        .bind(task_id)
+        .bind(project_id)
+        .bind(user_id)
+        .bind(action.as_str())
+        .bind(change.field)
+        .bind(change.old.as_deref())
+        .bind(change.new.as_deref())
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Rank placing a task at the bottom of a column.
+async fn end_of_column(
+    executor: impl Executor<'_, Database = Db>,
+    project_id: i64,
+    status: TaskStatus,
+) -> Result<String, sqlx::Error> {
+    let last: Option<String> = sqlx::query_scalar(&translate(
+        "SELECT MAX(board_rank) FROM tasks WHERE project_id = ? AND status = ?",
+    ))
+    .bind(project_id)
+    .bind(status.as_str())
+    .fetch_one(executor)
+    .await?;
+    Ok(rank_between(last.as_deref(), None))
+}
+
+/// Ids of every live descendant of `id`, excluding `id` itself.
+async fn subtree_ids(
+    executor: impl Executor<'_, Database = Db>,
+    id: i64,
+) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar(&translate(
+        "WITH RECURSIVE subtree (id) AS (
+            SELECT id FROM tasks WHERE parent_id = ? AND deleted_at IS NULL
+            UNION ALL
+            SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id WHERE t.deleted_at IS NULL
+         )
+         SELECT id FROM subtree",
+    ))
+    .bind(id)
+    .fetch_all(executor)
+    .await
+}
+
+/// Whether a live project of `user_id` is archived and read-only.
+async fn project_archived(
+    executor: impl Executor<'_, Database = Db>,
+    id: i64,
+    user_id: i64,
+) -> Result<bool, RepoError> {
+    let archived = sqlx::query_scalar(&translate(
+        "SELECT archived_at IS NOT NULL FROM projects
+         WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+    ))
+    .bind(id)
+    .bind(user_id)
+    .fetch_one(executor)
+    .await?;
+    Ok(archived)
+}
+
+/// Number of unfinished tasks `id` depends on.
+async fn open_blockers(
+    executor: impl Executor<'_, Database = Db>,
+    id: i64,
+) -> Result<i64, RepoError> {
+    let blockers = sqlx::query_scalar(&translate(
+        "SELECT COUNT(*) FROM task_dependencies d JOIN tasks t ON t.id = d.depends_on_id
+         WHERE d.task_id = ? AND t.status <> 'done' AND t.deleted_at IS NULL",
+    ))
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+    Ok(blockers)
+}
+
+/// Creates the next occurrence of a recurring task, at most once per task. With
+/// `after` the occurrences up to that moment are skipped. Returns the id of the
+/// new task, or `None` if there is nothing to create.
+async fn spawn_next_occurrence(
+    conn: &mut Connection,
+    task_id: i64,
+    after: Option<DateTime<Utc>>,
+) -> Result<Option<i64>, RepoError> {
+    let row = sqlx::query(&translate(
+        "SELECT user_id, project_id, parent_id, title, description, recurrence, occurrence, due_date,
+            next_occurrence_id, priority, story_points, estimate_hours
+         FROM tasks WHERE id = ? FOR UPDATE",
+    ))
+    .bind(task_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if opt_int(&row, "next_occurrence_id")?.is_some() {
+        return Ok(None);
+    }
+    let Some(rule) = get::<Option<String>>(&row, "recurrence")?
+        .and_then(|rule| rule.parse::<RecurrenceRule>().ok())
+    else {
+        return Ok(None);
+    };
+
+    // Without a due date the series continues from the moment it is rolled forward
+    let current = opt_date(&row, "due_date")?.unwrap_or_else(Utc::now);
+    let occurrence = int(&row, "occurrence")? as u32;
+    let next = match after {
+        Some(after) => rule.first_after(current, occurrence, after),
+        None => rule
+            .next_after(current, occurrence)
+            .map(|next| (next, occurrence + 1)),
+    };
+    let Some((next, next_occurrence)) = next else {
+        return Ok(None);
+    };
+    let project_id = int(&row, "project_id")?;
+    let user_id = int(&row, "user_id")?;
+    let rank = end_of_column(&mut *conn, project_id, TaskStatus::Todo).await?;
+
+    let next_id = insert_id(
+        &mut *conn,
+        sqlx::query(&translate_insert(
+            "INSERT INTO tasks (user_id, project_id, parent_id, title, description, status, due_date, recurrence,
+                occurrence, priority, story_points, estimate_hours, board_rank)
+             VALUES (?, ?, ?, ?, ?, 'todo', ?, ?, ?, ?, ?, ?, ?)",
+        ))
+        .bind(user_id)
+        .bind(project_id)
+        .bind(opt_int(&row, "parent_id")?)
+        .bind(get::<String>(&row, "title")?)
+        .bind(get::<Option<String>>(&row, "description")?)
+        .bind(time(next)?)
+        .bind(get::<Option<String>>(&row, "recurrence")?)
+        .bind(next_occurrence as i64)
+        .bind(get::<String>(&row, "priority")?)
+        .bind(opt_int(&row, "story_points")?)
+        .bind(get::<Option<f64>>(&row, "estimate_hours")?)
+        .bind(rank),
+    )
+    .await?;
+
+    sqlx::query(&translate(
+        "INSERT INTO task_labels (task_id, label) SELECT ?, label FROM task_labels WHERE task_id = ?",
+    ))
+    .bind(next_id)
+    .bind(task_id)
+    .execute(&mut *conn)
+    .await?;
+    record_activity(
+        &mut *conn,
+        next_id,
+        project_id,
+        user_id,
+        ActivityAction::Created,
+        &[],
+    )
+    .await?;
+
+    sqlx::query(&translate(
+        "UPDATE tasks SET next_occurrence_id = ? WHERE id = ?",
+    ))
+    .bind(next_id)
+    .bind(task_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(Some(next_id))
+}
+
+/// Moves `ids` to `project_id` and logs the move on each of them.
+async fn move_descendants(
+    conn: &mut Connection,
+    ids: &[i64],
+    project_id: i64,
+    user_id: i64,
+    change: &[FieldChange],
+) -> Result<(), sqlx::Error> {
+    for id in ids {
+        sqlx::query(&translate("UPDATE tasks SET project_id = ? WHERE id = ?"))
+            .bind(project_id)
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+        record_activity(
+            &mut *conn,
+            *id,
+            project_id,
+            user_id,
+            ActivityAction::Updated,
+            change,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// One trip to the trash. Everything a request deletes shares its id and is
+/// restored together.
+struct Deletion {
+    id: i64,
+    deleted_at: Time,
+}
+
+/// Starts a deletion of items owned by `user_id`.
+async fn start_deletion(conn: &mut Connection, user_id: i64) -> Result<Deletion, RepoError> {
+    let deleted_at = time(Utc::now().trunc_subsecs(0))?;
+    let id = insert_id(
+        &mut *conn,
+        sqlx::query(&translate_insert(
+            "INSERT INTO deletions (user_id, deleted_at) VALUES (?, ?)",
+        ))
+        .bind(user_id)
+        .bind(&deleted_at),
+    )
+    .await?;
+    Ok(Deletion { id, deleted_at })
+}
+
+/// Moves a task to the trash as part of `deletion`.
+async fn trash_task(
+    conn: &mut Connection,
+    task_id: i64,
+    deletion: &Deletion,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(&translate(
+        "UPDATE tasks SET deleted_at = ?, deletion_id = ? WHERE id = ?",
+    ))
+    .bind(&deletion.deleted_at)
+    .bind(deletion.id)
+    .bind(task_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
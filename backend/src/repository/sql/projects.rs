@@ -0,0 +1,201 @@
+use chrono::Utc;
+
+use crate::{
+    models::{
+        activity::ActivityAction,
+        project::{Project, StatusSummary},
+        tasks::Tasks,
+    },
+    repository::{ProjectRepository, RepoError},
+};
+
+use super::{
+    get, insert_id, int, int_sum, project_archived, project_from_row, record_activity,
+    start_deletion, sync_project, task_from_row, time, translate, translate_insert, Store,
+    TASK_COLUMNS,
+};
+
+#[rocket::async_trait]
+impl ProjectRepository for Store {
+    async fn create(&self, user_id: i64, name: &str) -> Result<Project, RepoError> {
+        let id = insert_id(
+            &self.db_pool,
+            sqlx::query(&translate_insert(
+                "INSERT INTO projects (name, user_id) VALUES (?, ?)",
+            ))
+            .bind(name)
+            .bind(user_id),
+        )
+        .await?;
+        sync_project(&self.db_pool, &self.search, id).await;
+        Ok(Project {
+            id,
+            user_id,
+            name: name.to_string(),
+            archived_at: None,
+        })
+    }
+
+    async fn find(&self, id: i64, user_id: i64) -> Result<Project, RepoError> {
+        let row = sqlx::query(&translate(
+            "SELECT id, user_id, name, archived_at FROM projects
+             WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        project_from_row(&row)
+    }
+
+    async fn archived(&self, id: i64, user_id: i64) -> Result<bool, RepoError> {
+        project_archived(&self.db_pool, id, user_id).await
+    }
+
+    async fn list(&self, user_id: i64, archived: bool) -> Result<Vec<Project>, RepoError> {
+        let rows = sqlx::query(&translate(
+            "SELECT id, user_id, name, archived_at FROM projects
+             WHERE user_id = ? AND deleted_at IS NULL AND (archived_at IS NOT NULL) = ?
+             ORDER BY name, id",
+        ))
+        .bind(user_id)
+        .bind(archived)
+        .fetch_all(&self.db_pool)
+        .await?;
+        rows.iter().map(project_from_row).collect()
+    }
+
+    async fn tasks(&self, id: i64, user_id: i64) -> Result<Vec<Tasks>, RepoError> {
+        self.find(id, user_id).await?;
+        let rows = sqlx::query(&translate(&format!(
+            "SELECT {} FROM tasks t WHERE t.project_id = ? AND t.user_id = ? AND t.deleted_at IS NULL
+             ORDER BY t.board_rank, t.id",
+            TASK_COLUMNS
+        )))
+        .bind(id)
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+        rows.iter().map(task_from_row).collect()
+    }
+
+    async fn dependencies(&self, id: i64, user_id: i64) -> Result<Vec<(i64, i64)>, RepoError> {
+        let rows = sqlx::query(&translate(
+            "SELECT d.task_id, d.depends_on_id FROM task_dependencies d
+             JOIN tasks t ON t.id = d.task_id
+             JOIN tasks u ON u.id = d.depends_on_id
+             WHERE t.project_id = ? AND u.project_id = ? AND t.user_id = ?
+                AND t.deleted_at IS NULL AND u.deleted_at IS NULL",
+        ))
+        .bind(id)
+        .bind(id)
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+        rows.iter()
+            .map(|row| Ok((int(row, "task_id")?, int(row, "depends_on_id")?)))
+            .collect()
+    }
+
+    async fn status_totals(&self, id: i64, user_id: i64) -> Result<Vec<StatusSummary>, RepoError> {
+        let rows = sqlx::query(&translate(&format!(
+            "SELECT status, COUNT(*) AS tasks, {} AS story_points,
+                COALESCE(SUM(estimate_hours), 0.0) AS estimate_hours
+             FROM tasks WHERE project_id = ? AND user_id = ? AND deleted_at IS NULL GROUP BY status",
+            int_sum("story_points")
+        )))
+        .bind(id)
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(StatusSummary {
+                    status: get::<String>(row, "status")?.parse().unwrap_or_default(),
+                    tasks: int(row, "tasks")?,
+                    story_points: int(row, "story_points")?,
+                    estimate_hours: get(row, "estimate_hours")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn set_archived(&self, id: i64, archived: bool) -> Result<(), RepoError> {
+        if archived {
+            sqlx::query(&translate(
+                "UPDATE projects SET archived_at = ? WHERE id = ? AND archived_at IS NULL",
+            ))
+            .bind(time(Utc::now())?)
+            .bind(id)
+            .execute(&self.db_pool)
+            .await?;
+        } else {
+            sqlx::query(&translate(
+                "UPDATE projects SET archived_at = NULL WHERE id = ?",
+            ))
+            .bind(id)
+            .execute(&self.db_pool)
+            .await?;
+        }
+        sync_project(&self.db_pool, &self.search, id).await;
+        Ok(())
+    }
+
+    async fn rename(&self, id: i64, user_id: i64, name: &str) -> Result<(), RepoError> {
+        let result = sqlx::query(&translate(
+            "UPDATE projects SET name = ? WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+        ))
+        .bind(name)
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.db_pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+        sync_project(&self.db_pool, &self.search, id).await;
+        Ok(())
+    }
+
+    async fn trash(&self, id: i64, user_id: i64) -> Result<(), RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        // The tasks share the project's deletion so restoring the project brings them back
+        let deletion = start_deletion(&mut tx, user_id).await?;
+
+        let result = sqlx::query(&translate(
+            "UPDATE projects SET deleted_at = ?, deletion_id = ?
+             WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+        ))
+        .bind(&deletion.deleted_at)
+        .bind(deletion.id)
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+
+        let task_ids: Vec<i64> = sqlx::query_scalar(&translate(
+            "SELECT id FROM tasks WHERE project_id = ? AND deleted_at IS NULL",
+        ))
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await?;
+        sqlx::query(&translate(
+            "UPDATE tasks SET deleted_at = ?, deletion_id = ? WHERE project_id = ? AND deleted_at IS NULL",
+        ))
+        .bind(&deletion.deleted_at)
+        .bind(deletion.id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        for task_id in task_ids {
+            record_activity(&mut tx, task_id, id, user_id, ActivityAction::Deleted, &[]).await?;
+        }
+
+        tx.commit().await?;
+        sync_project(&self.db_pool, &self.search, id).await;
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+
+use crate::{
+    models::search::DocKind,
+    repository::SearchRepository,
+    search::{SearchBackend, SearchDocument, SearchError},
+};
+
+use super::{get, int, translate, Pool, RepoError, Store};
+
+/// Live projects, all of them when `project_id` is `None`.
+async fn load_projects(
+    db_pool: &Pool,
+    project_id: Option<i64>,
+) -> Result<Vec<SearchDocument>, RepoError> {
+    let rows = sqlx::query(&translate(
+        "SELECT id, user_id, name, archived_at IS NOT NULL AS archived FROM projects
+         WHERE deleted_at IS NULL AND (? IS NULL OR id = ?)",
+    ))
+    .bind(project_id)
+    .bind(project_id)
+    .fetch_all(db_pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(SearchDocument {
+                kind: DocKind::Project,
+                id: int(row, "id")?,
+                user_id: int(row, "user_id")?,
+                project_id: int(row, "id")?,
+                task_id: None,
+                title: get(row, "name")?,
+                body: String::new(),
+                status: None,
+                priority: None,
+                archived: get(row, "archived")?,
+            })
+        })
+        .collect()
+}
+
+/// Live tasks in live projects, all of them when `task_id` is `None`.
+async fn load_tasks(
+    db_pool: &Pool,
+    task_id: Option<i64>,
+) -> Result<Vec<SearchDocument>, RepoError> {
+    let rows = sqlx::query(&translate(
+        "SELECT t.id, t.user_id, t.project_id, t.title, t.description, t.status,
+            t.priority, p.archived_at IS NOT NULL AS archived
+         FROM tasks t JOIN projects p ON p.id = t.project_id
+         WHERE t.deleted_at IS NULL AND p.deleted_at IS NULL AND (? IS NULL OR t.id = ?)",
+    ))
+    .bind(task_id)
+    .bind(task_id)
+    .fetch_all(db_pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(SearchDocument {
+                kind: DocKind::Task,
+                id: int(row, "id")?,
+                user_id: int(row, "user_id")?,
+                project_id: int(row, "project_id")?,
+                task_id: Some(int(row, "id")?),
+                title: get(row, "title")?,
+                body: get::<Option<String>>(row, "description")?.unwrap_or_default(),
+                status: Some(get(row, "status")?),
+                priority: Some(get(row, "priority")?),
+                archived: get(row, "archived")?,
+            })
+        })
+        .collect()
+}
+
+/// Live comments of live tasks, filtered by comment or by task.
+async fn load_comments(
+    db_pool: &Pool,
+    comment_id: Option<i64>,
+    task_id: Option<i64>,
+) -> Result<Vec<SearchDocument>, RepoError> {
+    let rows = sqlx::query(&translate(
+        "SELECT c.id, c.task_id, c.body, p.user_id, t.project_id, t.title, t.status,
+            t.priority, p.archived_at IS NOT NULL AS archived
+         FROM task_comments c JOIN tasks t ON t.id = c.task_id JOIN projects p ON p.id = t.project_id
+         WHERE c.deleted_at IS NULL AND t.deleted_at IS NULL AND p.deleted_at IS NULL
+            AND (? IS NULL OR c.id = ?) AND (? IS NULL OR c.task_id = ?)",
+    ))
+    .bind(comment_id)
+    .bind(comment_id)
+    .bind(task_id)
+    .bind(task_id)
+    .fetch_all(db_pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(SearchDocument {
+                kind: DocKind::Comment,
+                id: int(row, "id")?,
+                user_id: int(row, "user_id")?,
+                project_id: int(row, "project_id")?,
+                task_id: Some(int(row, "task_id")?),
+                title: get(row, "title")?,
+                body: get(row, "body")?,
+                status: Some(get(row, "status")?),
+                priority: Some(get(row, "priority")?),
+                archived: get(row, "archived")?,
+            })
+        })
+        .collect()
+}
+
+#[rocket::async_trait]
+impl SearchRepository for Store {
+    async fn documents(&self) -> Result<Vec<SearchDocument>, RepoError> {
+        let mut docs = load_projects(&self.db_pool, None).await?;
+        docs.extend(load_tasks(&self.db_pool, None).await?);
+        docs.extend(load_comments(&self.db_pool, None, None).await?);
+        Ok(docs)
+    }
+}
+
+async fn reindex_tasks(
+    db_pool: &Pool,
+    search: &SearchBackend,
+    task_ids: &[i64],
+) -> Result<(), SearchError> {
+    let mut docs = Vec::new();
+    let mut gone_tasks = Vec::new();
+    let mut gone_comments = Vec::new();
+    for &task_id in task_ids {
+        let task = load_tasks(db_pool, Some(task_id)).await?;
+        if task.is_empty() {
+            gone_tasks.push(task_id);
+        }
+        docs.extend(task);
+
+        let comments = load_comments(db_pool, None, Some(task_id)).await?;
+        let live: HashSet<i64> = comments.iter().map(|doc| doc.id).collect();
+        let all: Vec<i64> =
+            sqlx::query_scalar(&translate("SELECT id FROM task_comments WHERE task_id = ?"))
+                .bind(task_id)
+                .fetch_all(db_pool)
+                .await?;
+        gone_comments.extend(all.into_iter().filter(|id| !live.contains(id)));
+        docs.extend(comments);
+    }
+    search.remove(DocKind::Task, &gone_tasks).await?;
+    search.remove(DocKind::Comment, &gone_comments).await?;
+    search.upsert(docs).await
+}
+
+/// Brings an external index up to date with the given tasks and their comments.
+/// Called once the change is committed; a failure is only logged, the index is
+/// rebuilt from the database on the next start.
+pub(crate) async fn sync_tasks(db_pool: &Pool, search: &SearchBackend, task_ids: &[i64]) {
+    if !search.needs_sync() || task_ids.is_empty() {
+        return;
+    }
+    if let Err(e) = reindex_tasks(db_pool, search, task_ids).await {
+        tracing::warn!(error = ?e, "search index sync failed");
+    }
+}
+
+async fn reindex_project(
+    db_pool: &Pool,
+    search: &SearchBackend,
+    project_id: i64,
+) -> Result<(), SearchError> {
+    let project = load_projects(db_pool, Some(project_id)).await?;
+    if project.is_empty() {
+        search.remove(DocKind::Project, &[project_id]).await?;
+    } else {
+        search.upsert(project).await?;
+    }
+    // The archived flag and visibility of its tasks follow the project
+    let task_ids: Vec<i64> =
+        sqlx::query_scalar(&translate("SELECT id FROM tasks WHERE project_id = ?"))
+            .bind(project_id)
+            .fetch_all(db_pool)
+            .await?;
+    reindex_tasks(db_pool, search, &task_ids).await
+}
+
+/// Same as [`sync_tasks`] for a project, its tasks and their comments.
+pub(crate) async fn sync_project(db_pool: &Pool, search: &SearchBackend, project_id: i64) {
+    if !search.needs_sync() {
+        return;
+    }
+    if let Err(e) = reindex_project(db_pool, search, project_id).await {
+        tracing::warn!(error = ?e, "search index sync failed");
+    }
+}
+
+async fn reindex_comment(
+    db_pool: &Pool,
+    search: &SearchBackend,
+    comment_id: i64,
+) -> Result<(), SearchError> {
+    let comment = load_comments(db_pool, Some(comment_id), None).await?;
+    if comment.is_empty() {
+        search.remove(DocKind::Comment, &[comment_id]).await
+    } else {
+        search.upsert(comment).await
+    }
+}
+
+/// Same as [`sync_tasks`] for a single comment.
+pub(crate) async fn sync_comment(db_pool: &Pool, search: &SearchBackend, comment_id: i64) {
+    if !search.needs_sync() {
+        return;
+    }
+    if let Err(e) = reindex_comment(db_pool, search, comment_id).await {
+        tracing::warn!(error = ?e, "search index sync failed");
+    }
+}
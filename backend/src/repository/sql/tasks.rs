@@ -0,0 +1,553 @@
+use std::collections::{BTreeSet, HashSet};
+
+use chrono::Utc;
+
+use crate::{
+    models::{
+        activity::{diff, ActivityAction, FieldChange},
+        bulk::{BulkOperation, TaskFilter},
+        pagination::Pagination,
+        tasks::{BoardMove, ListOptions, SubtaskPolicy, TaskPage, TaskProgress, TaskStatus, Tasks},
+    },
+    repository::{RepoError, TaskRepository},
+    utils::{
+        filter::Filter,
+        rank::{rank_between, spread_ranks, MAX_RANK_LEN},
+    },
+};
+
+use super::{
+    bulk::apply_operation,
+    end_of_column, get, insert_id, int,
+    listing::{query_tasks, select_tasks},
+    move_descendants, open_blockers, record_activity, select_task, spawn_next_occurrence,
+    start_deletion, subtree_ids, sync_tasks, task_from_row, time, translate, translate_insert,
+    trash_task, Connection, Store, TASK_COLUMNS,
+};
+
+/// `task_id` and every task above it.
+async fn ancestors(conn: &mut Connection, task_id: i64) -> Result<BTreeSet<i64>, RepoError> {
+    let ids: Vec<i64> = sqlx::query_scalar(&translate(
+        "WITH RECURSIVE ancestors (id, parent_id) AS (
+            SELECT id, parent_id FROM tasks WHERE id = ?
+            UNION
+            SELECT t.id, t.parent_id FROM tasks t JOIN ancestors a ON t.id = a.parent_id
+         )
+         SELECT id FROM ancestors",
+    ))
+    .bind(task_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(ids.into_iter().collect())
+}
+
+#[rocket::async_trait]
+impl TaskRepository for Store {
+    async fn find(&self, id: i64, user_id: i64) -> Result<Tasks, RepoError> {
+        select_task(&self.db_pool, id, user_id).await
+    }
+
+    async fn children(&self, id: i64, user_id: i64) -> Result<Vec<Tasks>, RepoError> {
+        let rows = sqlx::query(&translate(&format!(
+            "SELECT {} FROM tasks t WHERE t.parent_id = ? AND t.user_id = ? AND t.deleted_at IS NULL
+             ORDER BY t.board_rank, t.id",
+            TASK_COLUMNS
+        )))
+        .bind(id)
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+        rows.iter().map(task_from_row).collect()
+    }
+
+    async fn progress(&self, id: i64) -> Result<TaskProgress, RepoError> {
+        let row = sqlx::query(&translate(
+            "WITH RECURSIVE subtree (id, status) AS (
+                SELECT id, status FROM tasks WHERE parent_id = ? AND deleted_at IS NULL
+                UNION ALL
+                SELECT t.id, t.status FROM tasks t JOIN subtree s ON t.parent_id = s.id
+                WHERE t.deleted_at IS NULL
+             )
+             SELECT COUNT(*) AS total, COUNT(CASE WHEN status = 'done' THEN 1 END) AS done
+             FROM subtree",
+        ))
+        .bind(id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        Ok(TaskProgress {
+            done: int(&row, "done")?,
+            total: int(&row, "total")?,
+        })
+    }
+
+    async fn subtree(&self, id: i64) -> Result<Vec<i64>, RepoError> {
+        Ok(subtree_ids(&self.db_pool, id).await?)
+    }
+
+    async fn open_blockers(&self, id: i64) -> Result<i64, RepoError> {
+        open_blockers(&self.db_pool, id).await
+    }
+
+    async fn create(&self, task: Tasks) -> Result<Tasks, RepoError> {
+        let due_date = task.due_date.map(time).transpose()?;
+
+        let mut tx = self.db_pool.begin().await?;
+        // New tasks go to the bottom of their column
+        let rank = end_of_column(&mut *tx, task.project_id, task.status).await?;
+        let id = insert_id(
+            &mut *tx,
+            sqlx::query(&translate_insert(
+                "INSERT INTO tasks (user_id, project_id, parent_id, title, description, status, priority, due_date,
+                    recurrence, story_points, estimate_hours, board_rank)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            ))
+            .bind(task.user_id)
+            .bind(task.project_id)
+            .bind(task.parent_id)
+            .bind(&task.title)
+            .bind(&task.description)
+            .bind(task.status.as_str())
+            .bind(task.priority.as_str())
+            .bind(due_date)
+            .bind(&task.recurrence)
+            .bind(task.story_points)
+            .bind(task.estimate_hours)
+            .bind(&rank),
+        )
+        .await?;
+        record_activity(
+            &mut tx,
+            id,
+            task.project_id,
+            task.user_id,
+            ActivityAction::Created,
+            &[],
+        )
+        .await?;
+        tx.commit().await?;
+
+        sync_tasks(&self.db_pool, &self.search, &[id]).await;
+        Ok(Tasks { id, rank, ..task })
+    }
+
+    async fn update(
+        &self,
+        previous: &Tasks,
+        task: Tasks,
+        changes: &[FieldChange],
+    ) -> Result<Tasks, RepoError> {
+        let due_date = task.due_date.map(time).transpose()?;
+        let project_changed = task.project_id != previous.project_id;
+
+        let mut tx = self.db_pool.begin().await?;
+        // A task landing in another column goes to its bottom
+        let rank = if project_changed || task.status != previous.status {
+            end_of_column(&mut *tx, task.project_id, task.status).await?
+        } else {
+            task.rank.clone()
+        };
+
+        sqlx::query(&translate(
+            "UPDATE tasks SET title = ?, description = ?, project_id = ?, parent_id = ?, status = ?, priority = ?,
+                due_date = ?, recurrence = ?, story_points = ?, estimate_hours = ?, board_rank = ?
+             WHERE id = ? AND user_id = ?",
+        ))
+        .bind(&task.title)
+        .bind(&task.description)
+        .bind(task.project_id)
+        .bind(task.parent_id)
+        .bind(task.status.as_str())
+        .bind(task.priority.as_str())
+        .bind(due_date)
+        .bind(&task.recurrence)
+        .bind(task.story_points)
+        .bind(task.estimate_hours)
+        .bind(&rank)
+        .bind(task.id)
+        .bind(task.user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let mut touched = vec![task.id];
+        if project_changed {
+            let descendants = subtree_ids(&mut *tx, task.id).await?;
+            let change = diff(
+                "project_id",
+                Some(&previous.project_id),
+                Some(&task.project_id),
+            );
+            move_descendants(
+                &mut tx,
+                &descendants,
+                task.project_id,
+                task.user_id,
+                change.as_slice(),
+            )
+            .await?;
+            touched.extend(descendants);
+        }
+
+        record_activity(
+            &mut tx,
+            task.id,
+            task.project_id,
+            task.user_id,
+            ActivityAction::Updated,
+            changes,
+        )
+        .await?;
+
+        // Completing an occurrence of a recurring task schedules the next one
+        if task.status == TaskStatus::Done && previous.status != TaskStatus::Done {
+            touched.extend(spawn_next_occurrence(&mut tx, task.id, None).await?);
+        }
+
+        tx.commit().await?;
+        sync_tasks(&self.db_pool, &self.search, &touched).await;
+        Ok(Tasks { rank, ..task })
+    }
+
+    async fn reparent(
+        &self,
+        task: &Tasks,
+        parent_id: Option<i64>,
+        project_id: i64,
+        changes: &[FieldChange],
+    ) -> Result<(), RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        if let Some(parent_id) = parent_id {
+            // Lock the task and the new parent with everything above it, in id order,
+            // until no move changed them in between. A move that could close a cycle
+            // concurrently involves one of them, so it waits for this one
+            let mut locked = BTreeSet::new();
+            let ancestors = loop {
+                let ancestors = ancestors(&mut tx, parent_id).await?;
+                let involved: BTreeSet<i64> = ancestors.iter().copied().chain([task.id]).collect();
+                if involved.is_subset(&locked) {
+                    break ancestors;
+                }
+                for id in involved.difference(&locked) {
+                    sqlx::query(&translate("SELECT id FROM tasks WHERE id = ? FOR UPDATE"))
+                        .bind(id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                locked.extend(involved);
+            };
+            if ancestors.contains(&task.id) {
+                return Err(RepoError::Conflict(
+                    "parent_cycle",
+                    "A task cannot be moved under itself or one of its subtasks",
+                ));
+            }
+        }
+        let descendants = subtree_ids(&mut *tx, task.id).await?;
+
+        sqlx::query(&translate(
+            "UPDATE tasks SET parent_id = ?, project_id = ? WHERE id = ? AND user_id = ?",
+        ))
+        .bind(parent_id)
+        .bind(project_id)
+        .bind(task.id)
+        .bind(task.user_id)
+        .execute(&mut *tx)
+        .await?;
+        record_activity(
+            &mut tx,
+            task.id,
+            project_id,
+            task.user_id,
+            ActivityAction::Updated,
+            changes,
+        )
+        .await?;
+
+        let mut touched = vec![task.id];
+        let project_change = diff("project_id", Some(&task.project_id), Some(&project_id));
+        if project_change.is_some() {
+            move_descendants(
+                &mut tx,
+                &descendants,
+                project_id,
+                task.user_id,
+                project_change.as_slice(),
+            )
+            .await?;
+            touched.extend(descendants);
+        }
+
+        tx.commit().await?;
+        sync_tasks(&self.db_pool, &self.search, &touched).await;
+        Ok(())
+    }
+
+    async fn trash(&self, task: &Tasks, subtasks: Option<SubtaskPolicy>) -> Result<(), RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        let deletion = start_deletion(&mut tx, task.user_id).await?;
+        let mut trashed = vec![task.id];
+
+        match subtasks {
+            None => {}
+            Some(SubtaskPolicy::Reparent) => {
+                let children: Vec<i64> =
+                    sqlx::query_scalar(&translate("SELECT id FROM tasks WHERE parent_id = ?"))
+                        .bind(task.id)
+                        .fetch_all(&mut *tx)
+                        .await?;
+                sqlx::query(&translate(
+                    "UPDATE tasks SET parent_id = ? WHERE parent_id = ?",
+                ))
+                .bind(task.parent_id)
+                .bind(task.id)
+                .execute(&mut *tx)
+                .await?;
+                let change = diff("parent_id", Some(&task.id), task.parent_id.as_ref());
+                for child in children {
+                    record_activity(
+                        &mut tx,
+                        child,
+                        task.project_id,
+                        task.user_id,
+                        ActivityAction::Updated,
+                        change.as_slice(),
+                    )
+                    .await?;
+                }
+            }
+            Some(SubtaskPolicy::Delete) => {
+                let descendants = subtree_ids(&mut *tx, task.id).await?;
+                for &descendant in &descendants {
+                    trash_task(&mut tx, descendant, &deletion).await?;
+                    record_activity(
+                        &mut tx,
+                        descendant,
+                        task.project_id,
+                        task.user_id,
+                        ActivityAction::Deleted,
+                        &[],
+                    )
+                    .await?;
+                }
+                trashed.extend(descendants);
+            }
+        }
+
+        trash_task(&mut tx, task.id, &deletion).await?;
+        record_activity(
+            &mut tx,
+            task.id,
+            task.project_id,
+            task.user_id,
+            ActivityAction::Deleted,
+            &[],
+        )
+        .await?;
+
+        tx.commit().await?;
+        sync_tasks(&self.db_pool, &self.search, &trashed).await;
+        Ok(())
+    }
+
+    async fn move_on_board(&self, task: &Tasks, target: &BoardMove) -> Result<Tasks, RepoError> {
+        let completing = target.status == TaskStatus::Done && task.status != TaskStatus::Done;
+        let mut tx = self.db_pool.begin().await?;
+
+        // Locking the target column serializes concurrent moves into it
+        let rows = sqlx::query(&translate(
+            "SELECT id, board_rank FROM tasks
+             WHERE project_id = ? AND status = ? AND id <> ? AND deleted_at IS NULL
+             ORDER BY board_rank, id FOR UPDATE",
+        ))
+        .bind(task.project_id)
+        .bind(target.status.as_str())
+        .bind(task.id)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut column = rows
+            .iter()
+            .map(|row| Ok((int(row, "id")?, get::<String>(row, "board_rank")?)))
+            .collect::<Result<Vec<_>, RepoError>>()?;
+
+        let index_of = |id: i64| {
+            column
+                .iter()
+                .position(|(other, _)| *other == id)
+                .ok_or(RepoError::Invalid(
+                    "not_in_column",
+                    "Neighbouring task is not in the target column",
+                ))
+        };
+        let slot = match (target.after, target.before) {
+            (Some(after), before) => {
+                let slot = index_of(after)? + 1;
+                if before.is_some() && column.get(slot).map(|(id, _)| *id) != before {
+                    return Err(RepoError::Conflict(
+                        "stale_neighbours",
+                        "The neighbouring tasks are no longer adjacent, reload the board",
+                    ));
+                }
+                slot
+            }
+            (None, Some(before)) => index_of(before)?,
+            (None, None) => column.len(),
+        };
+
+        let lower = slot.checked_sub(1).map(|i| column[i].1.as_str());
+        let upper = column.get(slot).map(|(_, rank)| rank.as_str());
+        let mut rank = rank_between(lower, upper);
+
+        // Equal neighbours or runaway ranks get the whole column renumbered
+        let fits = lower.is_none_or(|lower| lower < rank.as_str())
+            && upper.is_none_or(|upper| rank.as_str() < upper);
+        if !fits || rank.len() > MAX_RANK_LEN {
+            column.insert(slot, (task.id, String::new()));
+            let ranks = spread_ranks(column.len());
+            for ((id, _), new_rank) in column.iter().zip(&ranks) {
+                if *id == task.id {
+                    continue;
+                }
+                sqlx::query(&translate("UPDATE tasks SET board_rank = ? WHERE id = ?"))
+                    .bind(new_rank)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            rank = ranks[slot].clone();
+        }
+
+        sqlx::query(&translate(
+            "UPDATE tasks SET status = ?, board_rank = ? WHERE id = ?",
+        ))
+        .bind(target.status.as_str())
+        .bind(&rank)
+        .bind(task.id)
+        .execute(&mut *tx)
+        .await?;
+        let change = diff(
+            "status",
+            Some(&task.status.as_str()),
+            Some(&target.status.as_str()),
+        );
+        record_activity(
+            &mut tx,
+            task.id,
+            task.project_id,
+            task.user_id,
+            ActivityAction::Updated,
+            change.as_slice(),
+        )
+        .await?;
+
+        // Dropping a recurring task into the done column behaves like completing it
+        let mut touched = vec![task.id];
+        if completing {
+            touched.extend(spawn_next_occurrence(&mut tx, task.id, None).await?);
+        }
+
+        tx.commit().await?;
+        sync_tasks(&self.db_pool, &self.search, &touched).await;
+        Ok(Tasks {
+            status: target.status,
+            rank,
+            ..task.clone()
+        })
+    }
+
+    async fn query(
+        &self,
+        user_id: i64,
+        filter: &Filter,
+        options: &ListOptions,
+        pagination: &Pagination,
+    ) -> Result<TaskPage, RepoError> {
+        query_tasks(&self.db_pool, user_id, filter, options, pagination).await
+    }
+
+    async fn select(
+        &self,
+        user_id: i64,
+        filter: &TaskFilter,
+        limit: i64,
+    ) -> Result<Vec<i64>, RepoError> {
+        select_tasks(&self.db_pool, user_id, filter, limit).await
+    }
+
+    async fn bulk(
+        &self,
+        user_id: i64,
+        ids: &[i64],
+        operation: &BulkOperation,
+    ) -> Result<Vec<Result<(), RepoError>>, RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        // Tasks deleted by one request go to the trash and come back together
+        let deletion = match operation {
+            BulkOperation::Delete => Some(start_deletion(&mut tx, user_id).await?),
+            _ => None,
+        };
+        let mut trashed = HashSet::new();
+        let mut touched = ids.to_vec();
+        let mut results = Vec::with_capacity(ids.len());
+        for &id in ids {
+            // Already trashed as a subtask of an earlier item
+            if trashed.contains(&id) {
+                results.push(Ok(()));
+                continue;
+            }
+            results.push(
+                apply_operation(
+                    &mut tx,
+                    user_id,
+                    id,
+                    operation,
+                    deletion.as_ref(),
+                    &mut trashed,
+                    &mut touched,
+                )
+                .await,
+            );
+        }
+
+        if results.iter().any(Result::is_err) {
+            tx.rollback().await?;
+            return Ok(results);
+        }
+
+        tx.commit().await?;
+        // Labels are not part of the search index
+        if !matches!(operation, BulkOperation::AddLabel { .. }) {
+            sync_tasks(&self.db_pool, &self.search, &touched).await;
+        }
+        Ok(results)
+    }
+
+    async fn roll_overdue(&self) -> Result<usize, RepoError> {
+        let now = Utc::now();
+        let overdue: Vec<i64> = sqlx::query_scalar(&translate(
+            "SELECT t.id FROM tasks t JOIN projects p ON p.id = t.project_id
+             WHERE t.recurrence IS NOT NULL AND t.next_occurrence_id IS NULL AND t.due_date < ?
+                AND t.deleted_at IS NULL AND p.archived_at IS NULL",
+        ))
+        .bind(time(now)?)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        // Occurrences missed while the task sat overdue are skipped, not caught up one per run
+        let mut created = Vec::new();
+        for task_id in overdue {
+            // One broken series must not hold back every other one
+            let next = async {
+                let mut tx = self.db_pool.begin().await?;
+                let next = spawn_next_occurrence(&mut tx, task_id, Some(now)).await?;
+                tx.commit().await?;
+                Ok::<_, RepoError>(next)
+            }
+            .await;
+            match next {
+                Ok(next) => created.extend(next),
+                Err(e) => tracing::warn!(error = ?e, task_id, "failed to roll a recurring task"),
+            }
+        }
+        sync_tasks(&self.db_pool, &self.search, &created).await;
+        Ok(created.len())
+    }
+}
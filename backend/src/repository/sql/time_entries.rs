@@ -0,0 +1,199 @@
+use chrono::Utc;
+use sqlx::Executor;
+
+use crate::{
+    models::time_entry::{NewTimeEntry, TimeEntry},
+    repository::{RepoError, TimeEntryRepository, TimeQuery, TrackedTime},
+};
+
+use super::{
+    date, get, insert_id, int, opt_date, time, translate, translate_insert, Db, DbRow, Store,
+};
+
+fn timer_running() -> RepoError {
+    RepoError::Conflict("timer_running", "Another timer is already running")
+}
+
+fn entry_from_row(row: &DbRow) -> Result<TimeEntry, RepoError> {
+    Ok(TimeEntry {
+        id: int(row, "id")?,
+        task_id: int(row, "task_id")?,
+        user_id: int(row, "user_id")?,
+        started_at: date(row, "started_at")?,
+        ended_at: opt_date(row, "ended_at")?,
+        note: get(row, "note")?,
+    })
+}
+
+async fn select_entry(
+    executor: impl Executor<'_, Database = Db>,
+    id: i64,
+) -> Result<TimeEntry, RepoError> {
+    let row = sqlx::query(&translate(
+        "SELECT id, task_id, user_id, started_at, ended_at, note FROM time_entries WHERE id = ?",
+    ))
+    .bind(id)
+    .fetch_one(executor)
+    .await?;
+    entry_from_row(&row)
+}
+
+#[rocket::async_trait]
+impl TimeEntryRepository for Store {
+    async fn list(&self, task_id: i64) -> Result<Vec<TimeEntry>, RepoError> {
+        let rows = sqlx::query(&translate(
+            "SELECT id, task_id, user_id, started_at, ended_at, note FROM time_entries
+             WHERE task_id = ? ORDER BY started_at",
+        ))
+        .bind(task_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+        rows.iter().map(entry_from_row).collect()
+    }
+
+    async fn create(
+        &self,
+        task_id: i64,
+        user_id: i64,
+        entry: &NewTimeEntry,
+    ) -> Result<TimeEntry, RepoError> {
+        let id = insert_id(
+            &self.db_pool,
+            sqlx::query(&translate_insert(
+                "INSERT INTO time_entries (task_id, user_id, started_at, ended_at, note) VALUES (?, ?, ?, ?, ?)",
+            ))
+            .bind(task_id)
+            .bind(user_id)
+            .bind(time(entry.started_at)?)
+            .bind(time(entry.ended_at)?)
+            .bind(&entry.note),
+        )
+        .await?;
+
+        Ok(TimeEntry {
+            id,
+            task_id,
+            user_id,
+            started_at: entry.started_at,
+            ended_at: Some(entry.ended_at),
+            note: entry.note.clone(),
+        })
+    }
+
+    async fn delete(&self, id: i64, task_id: i64, user_id: i64) -> Result<(), RepoError> {
+        let result = sqlx::query(&translate(
+            "DELETE FROM time_entries WHERE id = ? AND task_id = ? AND user_id = ?",
+        ))
+        .bind(id)
+        .bind(task_id)
+        .bind(user_id)
+        .execute(&self.db_pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn start(&self, task_id: i64, user_id: i64) -> Result<TimeEntry, RepoError> {
+        let running: i64 = sqlx::query_scalar(&translate(
+            "SELECT COUNT(*) FROM time_entries WHERE user_id = ? AND ended_at IS NULL",
+        ))
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        if running > 0 {
+            return Err(timer_running());
+        }
+
+        // The unique index on running timers catches a concurrent start
+        let id = insert_id(
+            &self.db_pool,
+            sqlx::query(&translate_insert(
+                "INSERT INTO time_entries (task_id, user_id, started_at) VALUES (?, ?, ?)",
+            ))
+            .bind(task_id)
+            .bind(user_id)
+            .bind(time(Utc::now())?),
+        )
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(e) if e.is_unique_violation() => timer_running(),
+            e => RepoError::from(e),
+        })?;
+
+        select_entry(&self.db_pool, id).await
+    }
+
+    async fn stop(&self, task_id: i64, user_id: i64) -> Result<TimeEntry, RepoError> {
+        let row = sqlx::query(&translate(
+            "SELECT id FROM time_entries WHERE task_id = ? AND user_id = ? AND ended_at IS NULL",
+        ))
+        .bind(task_id)
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        let id = int(&row, "id")?;
+
+        sqlx::query(&translate(
+            "UPDATE time_entries SET ended_at = ? WHERE id = ?",
+        ))
+        .bind(time(Utc::now())?)
+        .bind(id)
+        .execute(&self.db_pool)
+        .await?;
+
+        select_entry(&self.db_pool, id).await
+    }
+
+    async fn tracked(
+        &self,
+        owner_id: i64,
+        query: &TimeQuery,
+    ) -> Result<Vec<TrackedTime>, RepoError> {
+        let from = query.from.map(time).transpose()?;
+        let until = query.until.map(time).transpose()?;
+        let rows = sqlx::query(&translate(
+            "SELECT e.started_at, e.ended_at, t.id AS task_id, t.title, p.id AS project_id,
+                p.name AS project_name, u.id AS user_id, u.username
+             FROM time_entries e
+             JOIN tasks t ON t.id = e.task_id
+             JOIN projects p ON p.id = t.project_id
+             JOIN users u ON u.id = e.user_id
+             WHERE p.user_id = ? AND t.deleted_at IS NULL
+                AND (? IS NULL OR p.id = ?)
+                AND (? IS NULL OR t.id = ?)
+                AND (? IS NULL OR e.user_id = ?)
+                AND (? IS NULL OR e.started_at >= ?)
+                AND (? IS NULL OR e.started_at < ?)",
+        ))
+        .bind(owner_id)
+        .bind(query.project_id)
+        .bind(query.project_id)
+        .bind(query.task_id)
+        .bind(query.task_id)
+        .bind(query.user_id)
+        .bind(query.user_id)
+        .bind(&from)
+        .bind(&from)
+        .bind(&until)
+        .bind(&until)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(TrackedTime {
+                    started_at: date(row, "started_at")?,
+                    ended_at: opt_date(row, "ended_at")?,
+                    task_id: int(row, "task_id")?,
+                    title: get(row, "title")?,
+                    project_id: int(row, "project_id")?,
+                    project_name: get(row, "project_name")?,
+                    user_id: int(row, "user_id")?,
+                    username: get(row, "username")?,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,255 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    models::{
+        activity::ActivityAction,
+        project::Project,
+        trash::{Trash, TrashedProject, TrashedTask},
+    },
+    repository::{RepoError, TrashRepository},
+};
+
+use super::{
+    date, get, int, opt_date, opt_int, project_archived, record_activity, sync_project, sync_tasks,
+    time, translate, Store,
+};
+
+fn project_archived_error() -> RepoError {
+    RepoError::Conflict("project_archived", "Project is archived and read-only")
+}
+
+#[rocket::async_trait]
+impl TrashRepository for Store {
+    async fn list(&self, user_id: i64) -> Result<Trash, RepoError> {
+        let projects = sqlx::query(&translate(
+            "SELECT id, name, deleted_at FROM projects
+             WHERE user_id = ? AND deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+        ))
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        // Only the roots of each deletion, the rest comes back with them
+        let tasks = sqlx::query(&translate(
+            "SELECT t.id, t.project_id, t.title, t.deleted_at FROM tasks t
+             JOIN projects p ON p.id = t.project_id
+             LEFT JOIN tasks parent ON parent.id = t.parent_id
+             WHERE t.user_id = ? AND t.deleted_at IS NOT NULL AND p.deleted_at IS NULL
+                AND (parent.deletion_id IS NULL OR parent.deletion_id <> t.deletion_id)
+             ORDER BY t.deleted_at DESC",
+        ))
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(Trash {
+            projects: projects
+                .iter()
+                .map(|row| {
+                    Ok(TrashedProject {
+                        id: int(row, "id")?,
+                        name: get(row, "name")?,
+                        deleted_at: date(row, "deleted_at")?,
+                    })
+                })
+                .collect::<Result<_, RepoError>>()?,
+            tasks: tasks
+                .iter()
+                .map(|row| {
+                    Ok(TrashedTask {
+                        id: int(row, "id")?,
+                        project_id: int(row, "project_id")?,
+                        title: get(row, "title")?,
+                        deleted_at: date(row, "deleted_at")?,
+                    })
+                })
+                .collect::<Result<_, RepoError>>()?,
+        })
+    }
+
+    async fn restore_task(&self, id: i64, user_id: i64) -> Result<(), RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        let row = sqlx::query(&translate(
+            "SELECT t.project_id, t.parent_id, t.deletion_id, p.deleted_at AS project_deleted_at
+             FROM tasks t JOIN projects p ON p.id = t.project_id
+             WHERE t.id = ? AND t.user_id = ? AND t.deleted_at IS NOT NULL FOR UPDATE",
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if opt_date(&row, "project_deleted_at")?.is_some() {
+            return Err(RepoError::Conflict(
+                "project_in_trash",
+                "The task's project is in the trash, restore the project instead",
+            ));
+        }
+        let project_id = int(&row, "project_id")?;
+        if project_archived(&mut *tx, project_id, user_id).await? {
+            return Err(project_archived_error());
+        }
+        let deletion_id = opt_int(&row, "deletion_id")?;
+
+        let parent_id = match opt_int(&row, "parent_id")? {
+            Some(parent_id) => sqlx::query(&translate(
+                "SELECT id FROM tasks WHERE id = ? AND deleted_at IS NULL",
+            ))
+            .bind(parent_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .map(|_| parent_id),
+            None => None,
+        };
+
+        let cascaded: Vec<i64> = sqlx::query_scalar(&translate(
+            "WITH RECURSIVE subtree (id) AS (
+                SELECT id FROM tasks WHERE parent_id = ? AND deletion_id = ?
+                UNION ALL
+                SELECT t.id FROM tasks t JOIN subtree s ON t.parent_id = s.id WHERE t.deletion_id = ?
+             )
+             SELECT id FROM subtree",
+        ))
+        .bind(id)
+        .bind(deletion_id)
+        .bind(deletion_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query(&translate(
+            "UPDATE tasks SET deleted_at = NULL, deletion_id = NULL, parent_id = ? WHERE id = ?",
+        ))
+        .bind(parent_id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        record_activity(
+            &mut tx,
+            id,
+            project_id,
+            user_id,
+            ActivityAction::Restored,
+            &[],
+        )
+        .await?;
+
+        let mut restored = vec![id];
+        for task_id in cascaded {
+            restored.push(task_id);
+            sqlx::query(&translate(
+                "UPDATE tasks SET deleted_at = NULL, deletion_id = NULL WHERE id = ?",
+            ))
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+            record_activity(
+                &mut tx,
+                task_id,
+                project_id,
+                user_id,
+                ActivityAction::Restored,
+                &[],
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+        sync_tasks(&self.db_pool, &self.search, &restored).await;
+        Ok(())
+    }
+
+    async fn restore_project(&self, id: i64, user_id: i64) -> Result<Project, RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        let row = sqlx::query(&translate(
+            "SELECT name, archived_at, deletion_id FROM projects
+             WHERE id = ? AND user_id = ? AND deleted_at IS NOT NULL FOR UPDATE",
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let deletion_id = opt_int(&row, "deletion_id")?;
+
+        let task_ids: Vec<i64> = sqlx::query_scalar(&translate(
+            "SELECT id FROM tasks WHERE project_id = ? AND deletion_id = ?",
+        ))
+        .bind(id)
+        .bind(deletion_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query(&translate(
+            "UPDATE projects SET deleted_at = NULL, deletion_id = NULL WHERE id = ?",
+        ))
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(&translate(
+            "UPDATE tasks SET deleted_at = NULL, deletion_id = NULL
+             WHERE project_id = ? AND deletion_id = ?",
+        ))
+        .bind(id)
+        .bind(deletion_id)
+        .execute(&mut *tx)
+        .await?;
+        for task_id in task_ids {
+            record_activity(&mut tx, task_id, id, user_id, ActivityAction::Restored, &[]).await?;
+        }
+
+        tx.commit().await?;
+        sync_project(&self.db_pool, &self.search, id).await;
+
+        Ok(Project {
+            id,
+            user_id,
+            name: get(&row, "name")?,
+            archived_at: opt_date(&row, "archived_at")?,
+        })
+    }
+
+    async fn purge(&self, cutoff: DateTime<Utc>) -> Result<Vec<String>, RepoError> {
+        let cutoff = time(cutoff)?;
+        let mut tx = self.db_pool.begin().await?;
+        let blobs = sqlx::query_scalar(&translate(
+            "SELECT DISTINCT a.sha256 FROM task_attachments a
+             JOIN tasks t ON t.id = a.task_id
+             JOIN projects p ON p.id = t.project_id
+             WHERE t.deleted_at < ? OR p.deleted_at < ?",
+        ))
+        .bind(&cutoff)
+        .bind(&cutoff)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        // Purging an occurrence clears `next_occurrence_id` of the one before it, the
+        // series ends there instead of bringing the deleted occurrence back. The
+        // derived table keeps MySQL from refusing a subquery on the updated table
+        sqlx::query(&translate(
+            "UPDATE tasks SET recurrence = NULL
+             WHERE next_occurrence_id IN (
+                SELECT id FROM (
+                    SELECT t.id FROM tasks t JOIN projects p ON p.id = t.project_id
+                    WHERE t.deleted_at < ? OR p.deleted_at < ?
+                ) purged
+             )",
+        ))
+        .bind(&cutoff)
+        .bind(&cutoff)
+        .execute(&mut *tx)
+        .await?;
+        // Tasks, comments, attachments and time entries follow through the foreign keys
+        sqlx::query(&translate("DELETE FROM projects WHERE deleted_at < ?"))
+            .bind(&cutoff)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&translate("DELETE FROM tasks WHERE deleted_at < ?"))
+            .bind(&cutoff)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&translate("DELETE FROM deletions WHERE deleted_at < ?"))
+            .bind(&cutoff)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(blobs)
+    }
+}
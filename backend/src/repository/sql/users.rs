@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    models::user::User,
+    repository::{PendingSignup, RepoError, UserAccount, UserRepository},
+};
+
+use super::{get, insert_id, int, time, translate, translate_insert, Store};
+
+#[rocket::async_trait]
+impl UserRepository for Store {
+    async fn create(&self, email: &str, username: &str, password: &str) -> Result<User, RepoError> {
+        let id = insert_id(
+            &self.db_pool,
+            sqlx::query(&translate_insert(
+                "INSERT INTO users (email, username, password) VALUES (?, ?, ?)",
+            ))
+            .bind(email)
+            .bind(username)
+            .bind(password),
+        )
+        .await?;
+        Ok(User {
+            id,
+            username: username.to_string(),
+            email: email.to_string(),
+            role: "user".to_string(),
+        })
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<UserAccount>, RepoError> {
+        let row = sqlx::query(&translate(
+            "SELECT id, username, email, password FROM users WHERE email = ?",
+        ))
+        .bind(email)
+        .fetch_optional(&self.db_pool)
+        .await?;
+        row.map(|row| {
+            Ok(UserAccount {
+                user: User {
+                    id: int(&row, "id")?,
+                    username: get(&row, "username")?,
+                    email: get(&row, "email")?,
+                    role: "user".to_string(),
+                },
+                password: get(&row, "password")?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn create_pending(
+        &self,
+        signup: &PendingSignup,
+        expired_before: DateTime<Utc>,
+    ) -> Result<(), RepoError> {
+        sqlx::query(&translate(
+            "DELETE FROM pending_signups WHERE created_at < ?",
+        ))
+        .bind(time(expired_before)?)
+        .execute(&self.db_pool)
+        .await?;
+        sqlx::query(&translate(
+            "INSERT INTO pending_signups (email, username, password, token_hash, created_at)
+             VALUES (?, ?, ?, ?, ?)",
+        ))
+        .bind(&signup.email)
+        .bind(&signup.username)
+        .bind(&signup.password)
+        .bind(&signup.token_hash)
+        .bind(time(Utc::now())?)
+        .execute(&self.db_pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn confirm_pending(
+        &self,
+        token_hash: &str,
+        created_after: DateTime<Utc>,
+    ) -> Result<User, RepoError> {
+        let mut tx = self.db_pool.begin().await?;
+        let row = sqlx::query(&translate(
+            "SELECT email, username, password FROM pending_signups
+             WHERE token_hash = ? AND created_at >= ? FOR UPDATE",
+        ))
+        .bind(token_hash)
+        .bind(time(created_after)?)
+        .fetch_one(&mut *tx)
+        .await?;
+        let email: String = get(&row, "email")?;
+        let username: String = get(&row, "username")?;
+
+        let id = insert_id(
+            &mut *tx,
+            sqlx::query(&translate_insert(
+                "INSERT INTO users (email, username, password) VALUES (?, ?, ?)",
+            ))
+            .bind(&email)
+            .bind(&username)
+            .bind(get::<String>(&row, "password")?),
+        )
+        .await?;
+        // Other sign-ups of the address are moot once one is confirmed
+        sqlx::query(&translate("DELETE FROM pending_signups WHERE email = ?"))
+            .bind(&email)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(User {
+            id,
+            username,
+            email,
+            role: "user".to_string(),
+        })
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    models::view::{NewSavedView, SavedView},
+    repository::{RepoError, ViewRepository},
+};
+
+use super::{date, get, insert_id, int, opt_int, translate, translate_insert, DbRow, Store};
+
+const VIEW_COLUMNS: &str = "v.id, v.user_id, v.project_id, v.name, v.query, v.sort, v.descending,
+    v.display, v.include_archived, v.created_at";
+
+fn view_from_row(row: &DbRow) -> Result<SavedView, RepoError> {
+    Ok(SavedView {
+        id: int(row, "id")?,
+        user_id: int(row, "user_id")?,
+        project_id: opt_int(row, "project_id")?,
+        name: get(row, "name")?,
+        query: get(row, "query")?,
+        sort: get::<String>(row, "sort")?.parse().unwrap_or_default(),
+        descending: get(row, "descending")?,
+        display: get::<String>(row, "display")?.parse().unwrap_or_default(),
+        include_archived: get(row, "include_archived")?,
+        created_at: date(row, "created_at")?,
+    })
+}
+
+#[rocket::async_trait]
+impl ViewRepository for Store {
+    async fn list(
+        &self,
+        user_id: i64,
+        project_id: Option<i64>,
+    ) -> Result<Vec<SavedView>, RepoError> {
+        let rows = sqlx::query(&translate(&format!(
+            "SELECT {} FROM saved_views v LEFT JOIN projects p ON p.id = v.project_id
+             WHERE (v.project_id IS NULL OR p.deleted_at IS NULL) AND (v.user_id = ? OR p.user_id = ?)
+                AND (? IS NULL OR v.project_id = ?)
+             ORDER BY v.name, v.id",
+            VIEW_COLUMNS
+        )))
+        .bind(user_id)
+        .bind(user_id)
+        .bind(project_id)
+        .bind(project_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+        rows.iter().map(view_from_row).collect()
+    }
+
+    async fn find(&self, id: i64, user_id: i64) -> Result<SavedView, RepoError> {
+        let row = sqlx::query(&translate(&format!(
+            "SELECT {} FROM saved_views v LEFT JOIN projects p ON p.id = v.project_id
+             WHERE v.id = ? AND (v.project_id IS NULL OR p.deleted_at IS NULL)
+                AND (v.user_id = ? OR p.user_id = ?)",
+            VIEW_COLUMNS
+        )))
+        .bind(id)
+        .bind(user_id)
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        view_from_row(&row)
+    }
+
+    async fn create(&self, user_id: i64, view: &NewSavedView) -> Result<SavedView, RepoError> {
+        let id = insert_id(
+            &self.db_pool,
+            sqlx::query(&translate_insert(
+                "INSERT INTO saved_views (user_id, project_id, name, query, sort, descending, display, include_archived)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            ))
+            .bind(user_id)
+            .bind(view.project_id)
+            .bind(&view.name)
+            .bind(view.query.as_deref().unwrap_or_default())
+            .bind(view.sort.unwrap_or_default().as_str())
+            .bind(view.descending.unwrap_or(false))
+            .bind(view.display.unwrap_or_default().as_str())
+            .bind(view.include_archived.unwrap_or(false)),
+        )
+        .await?;
+        self.find(id, user_id).await
+    }
+
+    async fn update(
+        &self,
+        id: i64,
+        user_id: i64,
+        view: &NewSavedView,
+    ) -> Result<SavedView, RepoError> {
+        sqlx::query(&translate(
+            "UPDATE saved_views SET project_id = ?, name = ?, query = ?, sort = ?, descending = ?, display = ?,
+                include_archived = ?
+             WHERE id = ? AND user_id = ?",
+        ))
+        .bind(view.project_id)
+        .bind(&view.name)
+        .bind(view.query.as_deref().unwrap_or_default())
+        .bind(view.sort.unwrap_or_default().as_str())
+        .bind(view.descending.unwrap_or(false))
+        .bind(view.display.unwrap_or_default().as_str())
+        .bind(view.include_archived.unwrap_or(false))
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.db_pool)
+        .await?;
+        self.find(id, user_id).await
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), RepoError> {
+        sqlx::query(&translate("DELETE FROM saved_views WHERE id = ?"))
+            .bind(id)
+            .execute(&self.db_pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use std::borrow::Cow;
+
+use chrono::{DateTime, Utc};
+use sqlx::{query::Query, sqlite::SqliteArguments, Executor, Sqlite};
+
+use crate::db::SqliteDB;
+
+use super::RepoError;
+
+// Every backend compiles its own copy of the shared implementation
+#[allow(clippy::duplicate_mod)]
+#[path = "sql/mod.rs"]
+mod sql;
+
+pub use self::sql::Store;
+
+type Db = Sqlite;
+type Pool = SqliteDB;
+/// How dates are bound, as text in the format `CURRENT_TIMESTAMP` uses so they
+/// compare correctly.
+type Time = String;
+
+/// SQLite locks the whole database for the first write of a transaction and has
+/// no `FOR UPDATE`, and spells `INSERT IGNORE` as `INSERT OR IGNORE`.
+fn translate(query: &str) -> Cow<'_, str> {
+    if !query.contains(" FOR UPDATE") && !query.starts_with("INSERT IGNORE") {
+        return Cow::Borrowed(query);
+    }
+    Cow::Owned(
+        query
+            .replace(" FOR UPDATE", "")
+            .replacen("INSERT IGNORE", "INSERT OR IGNORE", 1),
+    )
+}
+
+fn translate_insert(query: &str) -> Cow<'_, str> {
+    translate(query)
+}
+
+/// Runs an insert built from `translate_insert` and returns the new id.
+async fn insert_id<'c, 'q>(
+    executor: impl Executor<'c, Database = Db>,
+    query: Query<'q, Db, SqliteArguments<'q>>,
+) -> Result<i64, sqlx::Error> {
+    Ok(query.execute(executor).await?.last_insert_rowid())
+}
+
+fn time(date: DateTime<Utc>) -> Result<Time, RepoError> {
+    Ok(date.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+fn int_sum(column: &str) -> String {
+    format!("COALESCE(SUM({}), 0)", column)
+}
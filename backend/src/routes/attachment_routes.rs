@@ -0,0 +1,12 @@
+use crate::handlers::attachment_handler::{
+    delete_attachment, download_attachment, get_attachments, upload_attachment,
+};
+use rocket::Route;
+pub fn attachment_routes() -> Vec<Route> {
+    routes![
+        get_attachments,
+        upload_attachment,
+        download_attachment,
+        delete_attachment
+    ]
+}
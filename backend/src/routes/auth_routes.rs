@@ -1,7 +1,7 @@
 use rocket::Route;
 
-use crate::handlers::auth_handlers::{sign_in, sign_up};
+use crate::handlers::auth_handlers::{confirm_sign_up, sign_in, sign_up};
 
 pub fn auth_routes() -> Vec<Route> {
-    routes![sign_in, sign_up]
+    routes![sign_in, sign_up, confirm_sign_up]
 }
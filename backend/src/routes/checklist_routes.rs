@@ -0,0 +1,14 @@
+use crate::handlers::checklist_handler::{
+    add_checklist_item, delete_checklist_item, get_checklist, reorder_checklist,
+    toggle_checklist_item,
+};
+use rocket::Route;
+pub fn checklist_routes() -> Vec<Route> {
+    routes![
+        get_checklist,
+        add_checklist_item,
+        reorder_checklist,
+        toggle_checklist_item,
+        delete_checklist_item
+    ]
+}
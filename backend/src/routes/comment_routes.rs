@@ -0,0 +1,7 @@
+use crate::handlers::comment_handler::{
+    create_comment, delete_comment, get_comments, update_comment,
+};
+use rocket::Route;
+pub fn comment_routes() -> Vec<Route> {
+    routes![get_comments, create_comment, update_comment, delete_comment]
+}
@@ -0,0 +1,5 @@
+use crate::handlers::dependency_handler::{add_dependency, get_dependencies, remove_dependency};
+use rocket::Route;
+pub fn dependency_routes() -> Vec<Route> {
+    routes![get_dependencies, add_dependency, remove_dependency]
+}
@@ -0,0 +1,5 @@
+use crate::handlers::label_handler::{add_label, get_labels, remove_label};
+use rocket::Route;
+pub fn label_routes() -> Vec<Route> {
+    routes![get_labels, add_label, remove_label]
+}
@@ -0,0 +1,5 @@
+use crate::handlers::metrics_handler::metrics;
+use rocket::Route;
+pub fn metrics_routes() -> Vec<Route> {
+    routes![metrics]
+}
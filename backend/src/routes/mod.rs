@@ -1,3 +1,14 @@
+pub mod attachment_routes;
 pub mod auth_routes;
+pub mod checklist_routes;
+pub mod comment_routes;
+pub mod dependency_routes;
+pub mod label_routes;
+pub mod metrics_routes;
 pub mod project_routes;
+pub mod report_routes;
+pub mod search_routes;
 pub mod tasks_routes;
+pub mod time_routes;
+pub mod trash_routes;
+pub mod view_routes;
@@ -1,13 +1,25 @@
-use crate::handlers::project_handler::{
-    create_project, delete_project, get_project, get_project_tasks, update_project,
+use crate::handlers::{
+    activity_handler::get_project_activity,
+    board_handler::get_board,
+    project_handler::{
+        archive_project, create_project, delete_project, get_project, get_project_schedule,
+        get_project_summary, get_project_tasks, get_projects, unarchive_project, update_project,
+    },
 };
 use rocket::Route;
 pub fn project_routes() -> Vec<Route> {
     routes![
         create_project,
+        get_projects,
         get_project,
         get_project_tasks,
+        get_project_schedule,
+        get_project_summary,
+        get_board,
+        get_project_activity,
         update_project,
+        archive_project,
+        unarchive_project,
         delete_project
     ]
 }
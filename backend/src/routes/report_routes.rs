@@ -0,0 +1,5 @@
+use crate::handlers::report_handler::time_report;
+use rocket::Route;
+pub fn report_routes() -> Vec<Route> {
+    routes![time_report]
+}
@@ -0,0 +1,5 @@
+use crate::handlers::search_handler::search;
+use rocket::Route;
+pub fn search_routes() -> Vec<Route> {
+    routes![search]
+}
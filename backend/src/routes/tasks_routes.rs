@@ -1,5 +1,24 @@
-use crate::handlers::task_handler::{create_task, delete_task, get_tasks, update_task};
+use crate::handlers::{
+    activity_handler::get_task_history,
+    board_handler::move_on_board,
+    bulk_handler::bulk_update,
+    filter_handler::list_tasks,
+    task_handler::{
+        create_task, delete_task, get_task_children, get_tasks, move_task, update_task,
+    },
+};
 use rocket::Route;
 pub fn tasks_routes() -> Vec<Route> {
-    routes![create_task, get_tasks, update_task, delete_task]
+    routes![
+        create_task,
+        list_tasks,
+        get_tasks,
+        get_task_children,
+        update_task,
+        move_task,
+        move_on_board,
+        get_task_history,
+        bulk_update,
+        delete_task
+    ]
 }
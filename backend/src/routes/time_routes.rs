@@ -0,0 +1,13 @@
+use crate::handlers::time_handler::{
+    create_time_entry, delete_time_entry, get_time_entries, start_timer, stop_timer,
+};
+use rocket::Route;
+pub fn time_routes() -> Vec<Route> {
+    routes![
+        get_time_entries,
+        create_time_entry,
+        delete_time_entry,
+        start_timer,
+        stop_timer
+    ]
+}
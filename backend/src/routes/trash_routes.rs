@@ -0,0 +1,5 @@
+use crate::handlers::trash_handler::{get_trash, restore_project, restore_task};
+use rocket::Route;
+pub fn trash_routes() -> Vec<Route> {
+    routes![get_trash, restore_task, restore_project]
+}
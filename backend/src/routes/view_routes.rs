@@ -0,0 +1,14 @@
+use crate::handlers::view_handler::{
+    create_view, delete_view, get_view, get_views, run_view, update_view,
+};
+use rocket::Route;
+pub fn view_routes() -> Vec<Route> {
+    routes![
+        get_views,
+        get_view,
+        create_view,
+        update_view,
+        delete_view,
+        run_view
+    ]
+}
@@ -0,0 +1,304 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use rocket::tokio::task;
+use tantivy::{
+    collector::TopDocs,
+    directory::MmapDirectory,
+    query::{BooleanQuery, Occur, Query, QueryParser, TermQuery},
+    schema::{
+        Field, IndexRecordOption, Schema, TantivyDocument, Term, Value, INDEXED, STORED, STRING,
+        TEXT,
+    },
+    snippet::SnippetGenerator,
+    Index, IndexReader, IndexWriter, ReloadPolicy, TantivyError,
+};
+
+use crate::models::search::{DocKind, SearchHit};
+
+use super::{highlight, SearchDocument, SearchError, SearchIndex, SearchQuery, MAX_OFFSET};
+
+/// Memory used by the writer before it flushes a segment.
+const WRITER_HEAP_BYTES: usize = 50_000_000;
+
+impl From<TantivyError> for SearchError {
+    fn from(e: TantivyError) -> Self {
+        SearchError::Backend(e.to_string())
+    }
+}
+
+impl From<task::JoinError> for SearchError {
+    fn from(e: task::JoinError) -> Self {
+        SearchError::Backend(e.to_string())
+    }
+}
+
+struct Fields {
+    /// `<kind>:<id>`, unique per document so it can be replaced or deleted.
+    key: Field,
+    kind: Field,
+    id: Field,
+    user_id: Field,
+    project_id: Field,
+    task_id: Field,
+    title: Field,
+    body: Field,
+    status: Field,
+    priority: Field,
+    archived: Field,
+}
+
+impl Fields {
+    fn schema() -> (Schema, Fields) {
+        let mut builder = Schema::builder();
+        let fields = Fields {
+            key: builder.add_text_field("key", STRING),
+            kind: builder.add_text_field("kind", STRING | STORED),
+            id: builder.add_u64_field("id", STORED),
+            user_id: builder.add_u64_field("user_id", INDEXED),
+            project_id: builder.add_u64_field("project_id", INDEXED | STORED),
+            task_id: builder.add_u64_field("task_id", STORED),
+            title: builder.add_text_field("title", TEXT | STORED),
+            body: builder.add_text_field("body", TEXT | STORED),
+            status: builder.add_text_field("status", STRING),
+            priority: builder.add_text_field("priority", STRING),
+            archived: builder.add_u64_field("archived", INDEXED),
+        };
+        (builder.build(), fields)
+    }
+
+    fn key(kind: DocKind, id: i64) -> String {
+        format!("{}:{}", kind.as_str(), id)
+    }
+
+    fn document(&self, doc: SearchDocument) -> TantivyDocument {
+        let mut document = TantivyDocument::new();
+        document.add_text(self.key, Fields::key(doc.kind, doc.id));
+        document.add_text(self.kind, doc.kind.as_str());
+        document.add_u64(self.id, doc.id as u64);
+        document.add_u64(self.user_id, doc.user_id as u64);
+        document.add_u64(self.project_id, doc.project_id as u64);
+        if let Some(task_id) = doc.task_id {
+            document.add_u64(self.task_id, task_id as u64);
+        }
+        document.add_text(self.title, doc.title);
+        document.add_text(self.body, doc.body);
+        if let Some(status) = doc.status {
+            document.add_text(self.status, status);
+        }
+        if let Some(priority) = doc.priority {
+            document.add_text(self.priority, priority);
+        }
+        document.add_u64(self.archived, doc.archived as u64);
+        document
+    }
+}
+
+struct Inner {
+    index: Index,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+    fields: Fields,
+}
+
+impl Inner {
+    /// Applies the changes made by `change` and makes them visible to searches.
+    fn write(
+        &self,
+        change: impl FnOnce(&IndexWriter, &Fields) -> tantivy::Result<()>,
+    ) -> Result<(), SearchError> {
+        let mut writer = self.writer.lock().expect("search index writer");
+        change(&writer, &self.fields)?;
+        writer.commit()?;
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>, SearchError> {
+        if query.offset > MAX_OFFSET {
+            return Err(SearchError::InvalidQuery("offset is too deep".to_string()));
+        }
+        let fields = &self.fields;
+        let parser = QueryParser::for_index(&self.index, vec![fields.title, fields.body]);
+        let text_query = parser
+            .parse_query(&query.text)
+            .map_err(|e| SearchError::InvalidQuery(e.to_string()))?;
+
+        let term = |field: Field, value: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(field, value),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let number = |field: Field, value: u64| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_u64(field, value),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let mut clauses = vec![
+            (Occur::Must, text_query.box_clone()),
+            (Occur::Must, number(fields.user_id, query.user_id as u64)),
+        ];
+        if let Some(kind) = query.kind {
+            clauses.push((Occur::Must, term(fields.kind, kind.as_str())));
+        }
+        if let Some(project_id) = query.project_id {
+            clauses.push((Occur::Must, number(fields.project_id, project_id as u64)));
+        }
+        if let Some(status) = query.status {
+            clauses.push((Occur::Must, term(fields.status, status.as_str())));
+        }
+        if let Some(priority) = query.priority {
+            clauses.push((Occur::Must, term(fields.priority, priority.as_str())));
+        }
+        if !query.include_archived {
+            clauses.push((Occur::Must, number(fields.archived, 0)));
+        }
+        let filtered = BooleanQuery::new(clauses);
+
+        let searcher = self.reader.searcher();
+        let top_docs = searcher.search(
+            &filtered,
+            &TopDocs::with_limit(query.limit).and_offset(query.offset),
+        )?;
+        let mut title_snippets = SnippetGenerator::create(&searcher, &*text_query, fields.title)?;
+        let mut body_snippets = SnippetGenerator::create(&searcher, &*text_query, fields.body)?;
+        title_snippets.set_max_num_chars(200);
+        body_snippets.set_max_num_chars(200);
+
+        let mut hits = Vec::with_capacity(top_docs.len());
+        for (score, address) in top_docs {
+            let doc: TantivyDocument = searcher.doc(address)?;
+            let text = |field: Field| {
+                doc.get_first(field)
+                    .and_then(|value| value.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let number = |field: Field| doc.get_first(field).and_then(|value| value.as_u64());
+            let Some(kind) = DocKind::parse(&text(fields.kind)) else {
+                continue;
+            };
+
+            let mut snippet = body_snippets.snippet_from_doc(&doc);
+            if snippet.is_empty() {
+                snippet = title_snippets.snippet_from_doc(&doc);
+            }
+            let title = text(fields.title);
+            let snippet = if snippet.is_empty() {
+                highlight(&title, &query.text)
+            } else {
+                snippet.set_snippet_prefix_postfix("<mark>", "</mark>");
+                snippet.to_html()
+            };
+
+            hits.push(SearchHit {
+                kind,
+                id: number(fields.id).unwrap_or_default() as i64,
+                project_id: number(fields.project_id).unwrap_or_default() as i64,
+                task_id: number(fields.task_id).map(|id| id as i64),
+                title,
+                snippet,
+                score,
+            });
+        }
+        Ok(hits)
+    }
+}
+
+/// Embedded tantivy index on disk. It is not transactional with the database, so
+/// handlers sync it after each commit and it is rebuilt on start.
+pub struct TantivySearch {
+    inner: Arc<Inner>,
+}
+
+impl TantivySearch {
+    pub fn open(dir: PathBuf) -> Result<Self, SearchError> {
+        fs::create_dir_all(&dir).map_err(|e| SearchError::Backend(e.to_string()))?;
+        let directory =
+            MmapDirectory::open(&dir).map_err(|e| SearchError::Backend(e.to_string()))?;
+        let (schema, fields) = Fields::schema();
+        let index = Index::open_or_create(directory, schema)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let writer = index.writer(WRITER_HEAP_BYTES)?;
+        Ok(TantivySearch {
+            inner: Arc::new(Inner {
+                index,
+                reader,
+                writer: Mutex::new(writer),
+                fields,
+            }),
+        })
+    }
+}
+
+#[rocket::async_trait]
+impl SearchIndex for TantivySearch {
+    fn needs_sync(&self) -> bool {
+        true
+    }
+
+    async fn upsert(&self, docs: Vec<SearchDocument>) -> Result<(), SearchError> {
+        if docs.is_empty() {
+            return Ok(());
+        }
+        let inner = self.inner.clone();
+        task::spawn_blocking(move || {
+            inner.write(|writer, fields| {
+                for doc in docs {
+                    writer.delete_term(Term::from_field_text(
+                        fields.key,
+                        &Fields::key(doc.kind, doc.id),
+                    ));
+                    writer.add_document(fields.document(doc))?;
+                }
+                Ok(())
+            })
+        })
+        .await?
+    }
+
+    async fn remove(&self, kind: DocKind, ids: &[i64]) -> Result<(), SearchError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let inner = self.inner.clone();
+        let keys: Vec<String> = ids.iter().map(|&id| Fields::key(kind, id)).collect();
+        task::spawn_blocking(move || {
+            inner.write(|writer, fields| {
+                for key in keys {
+                    writer.delete_term(Term::from_field_text(fields.key, &key));
+                }
+                Ok(())
+            })
+        })
+        .await?
+    }
+
+    async fn rebuild(&self, docs: Vec<SearchDocument>) -> Result<(), SearchError> {
+        let inner = self.inner.clone();
+        task::spawn_blocking(move || {
+            inner.write(|writer, fields| {
+                writer.delete_all_documents()?;
+                for doc in docs {
+                    writer.add_document(fields.document(doc))?;
+                }
+                Ok(())
+            })
+        })
+        .await?
+    }
+
+    async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>, SearchError> {
+        let inner = self.inner.clone();
+        let query = query.clone();
+        task::spawn_blocking(move || inner.search(&query)).await?
+    }
+}
@@ -0,0 +1,177 @@
+use std::{env, fmt, path::PathBuf, sync::Arc};
+
+use crate::{
+    db::DB,
+    models::{
+        search::{DocKind, SearchHit},
+        tasks::{TaskPriority, TaskStatus},
+    },
+};
+
+use self::{embedded::TantivySearch, mysql::MySqlSearch};
+
+pub mod embedded;
+pub mod mysql;
+
+/// A searchable entity as stored in the index. Comments carry the status and
+/// priority of their task so they can be filtered the same way.
+pub struct SearchDocument {
+    pub kind: DocKind,
+    pub id: i64,
+    pub user_id: i64,
+    pub project_id: i64,
+    pub task_id: Option<i64>,
+    pub title: String,
+    pub body: String,
+    pub status: Option<String>,
+    pub priority: Option<String>,
+    pub archived: bool,
+}
+
+/// Deepest result offset a search may ask for, the embedded index allocates
+/// room for every result up to the requested page.
+pub const MAX_OFFSET: usize = 10_000;
+
+#[derive(Clone)]
+pub struct SearchQuery {
+    pub user_id: i64,
+    pub text: String,
+    pub kind: Option<DocKind>,
+    pub project_id: Option<i64>,
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    pub include_archived: bool,
+    /// At most [`MAX_OFFSET`].
+    pub offset: usize,
+    pub limit: usize,
+}
+
+#[derive(Debug)]
+pub enum SearchError {
+    InvalidQuery(String),
+    Backend(String),
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::InvalidQuery(e) => write!(f, "invalid query: {}", e),
+            SearchError::Backend(e) => write!(f, "search backend error: {}", e),
+        }
+    }
+}
+
+impl From<sqlx::Error> for SearchError {
+    fn from(e: sqlx::Error) -> Self {
+        SearchError::Backend(e.to_string())
+    }
+}
+
+/// Full text search over the projects, tasks and comments of a user.
+#[rocket::async_trait]
+pub trait SearchIndex: Send + Sync {
+    /// Whether the index lives outside the database and has to be told about
+    /// changes. When `false`, `upsert`, `remove` and `rebuild` are never called.
+    fn needs_sync(&self) -> bool;
+    /// Adds the documents, replacing any previous version.
+    async fn upsert(&self, docs: Vec<SearchDocument>) -> Result<(), SearchError>;
+    async fn remove(&self, kind: DocKind, ids: &[i64]) -> Result<(), SearchError>;
+    /// Replaces the whole content of the index.
+    async fn rebuild(&self, docs: Vec<SearchDocument>) -> Result<(), SearchError>;
+    /// Matches ordered by decreasing relevance.
+    async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>, SearchError>;
+}
+
+pub type SearchBackend = Arc<dyn SearchIndex>;
+
+/// `SEARCH_BACKEND` selects `mysql` (FULLTEXT indexes) or `tantivy`, an embedded
+/// index stored in `SEARCH_INDEX_DIR` (default `search-index`). FULLTEXT indexes
+/// are the default on MySQL and the only choice that needs its pool, the other
+/// databases always use tantivy.
+pub fn search_from_env(db_pool: Option<&DB>) -> SearchBackend {
+    match (env::var("SEARCH_BACKEND").as_deref(), db_pool) {
+        (Ok("mysql") | Err(_), Some(db_pool)) => Arc::new(MySqlSearch::new(db_pool.clone())),
+        _ => {
+            let dir = env::var("SEARCH_INDEX_DIR").unwrap_or_else(|_| "search-index".to_string());
+            Arc::new(TantivySearch::open(PathBuf::from(dir)).expect("Failed to open search index"))
+        }
+    }
+}
+
+/// Escapes `text` for HTML and wraps the words starting with one of the query
+/// terms in `<mark>`, keeping a window of words around the first match.
+pub fn highlight(text: &str, query: &str) -> String {
+    const BEFORE: usize = 10;
+    const WORDS: usize = 30;
+
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    // Alternating runs of word and non-word characters
+    let mut tokens: Vec<(bool, &str)> = Vec::new();
+    let mut start = 0;
+    let mut in_word = None;
+    for (i, c) in text.char_indices() {
+        let word = c.is_alphanumeric();
+        if in_word.is_some_and(|current| current != word) {
+            tokens.push((in_word.unwrap(), &text[start..i]));
+            start = i;
+        }
+        in_word = Some(word);
+    }
+    if let Some(word) = in_word {
+        tokens.push((word, &text[start..]));
+    }
+
+    let matches = |token: &str| {
+        let token = token.to_lowercase();
+        terms.iter().any(|term| token.starts_with(term.as_str()))
+    };
+    let words: Vec<usize> = (0..tokens.len()).filter(|&i| tokens[i].0).collect();
+    let first = words
+        .iter()
+        .position(|&i| matches(tokens[i].1))
+        .unwrap_or(0);
+    let from_word = first.saturating_sub(BEFORE);
+    let to_word = (from_word + WORDS).min(words.len());
+    let (from, to) = match (words.get(from_word), to_word.checked_sub(1)) {
+        (Some(&from), Some(last)) => (from, words[last] + 1),
+        _ => (0, tokens.len()),
+    };
+
+    let mut snippet = String::new();
+    if from > 0 {
+        snippet.push('…');
+    }
+    for &(word, token) in &tokens[from..to] {
+        if word && matches(token) {
+            snippet.push_str("<mark>");
+            snippet.push_str(&escape_html(token));
+            snippet.push_str("</mark>");
+        } else {
+            snippet.push_str(&escape_html(token));
+        }
+    }
+    if to < tokens.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,180 @@
+use std::cmp::Ordering;
+
+use sqlx::Row;
+
+use crate::{
+    db::DB,
+    models::search::{DocKind, SearchHit},
+};
+
+use super::{highlight, SearchDocument, SearchError, SearchIndex, SearchQuery};
+
+/// Search through the FULLTEXT indexes of the tables, always up to date since
+/// MySQL maintains them along with the data.
+pub struct MySqlSearch {
+    db_pool: DB,
+}
+
+impl MySqlSearch {
+    pub fn new(db_pool: DB) -> Self {
+        MySqlSearch { db_pool }
+    }
+}
+
+fn snippet(title: &str, body: &str, query: &str) -> String {
+    if body.trim().is_empty() {
+        highlight(title, query)
+    } else {
+        highlight(body, query)
+    }
+}
+
+#[rocket::async_trait]
+impl SearchIndex for MySqlSearch {
+    fn needs_sync(&self) -> bool {
+        false
+    }
+
+    async fn upsert(&self, _docs: Vec<SearchDocument>) -> Result<(), SearchError> {
+        Ok(())
+    }
+
+    async fn remove(&self, _kind: DocKind, _ids: &[i64]) -> Result<(), SearchError> {
+        Ok(())
+    }
+
+    async fn rebuild(&self, _docs: Vec<SearchDocument>) -> Result<(), SearchError> {
+        Ok(())
+    }
+
+    async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>, SearchError> {
+        let text = query.text.as_str();
+        let status = query.status.map(|status| status.as_str());
+        let priority = query.priority.map(|priority| priority.as_str());
+        // Each table is ranked on its own, enough rows are read from each to fill the page
+        let limit = (query.offset + query.limit) as i64;
+        let wants = |kind: DocKind| query.kind.is_none_or(|wanted| wanted == kind);
+        let mut hits = Vec::new();
+
+        // Projects have no status or priority, a filter on either excludes them
+        if wants(DocKind::Project) && status.is_none() && priority.is_none() {
+            let rows = sqlx::query(
+                "SELECT id, name, MATCH (name) AGAINST (?) AS score FROM projects
+                 WHERE user_id = ? AND deleted_at IS NULL AND MATCH (name) AGAINST (?)
+                    AND (? IS NULL OR id = ?) AND (? OR archived_at IS NULL)
+                 ORDER BY score DESC, id LIMIT ?",
+            )
+            .bind(text)
+            .bind(query.user_id)
+            .bind(text)
+            .bind(query.project_id)
+            .bind(query.project_id)
+            .bind(query.include_archived)
+            .bind(limit)
+            .fetch_all(&self.db_pool)
+            .await?;
+            for row in rows {
+                let id: i64 = row.try_get("id")?;
+                let name: String = row.try_get("name")?;
+                hits.push(SearchHit {
+                    kind: DocKind::Project,
+                    id,
+                    project_id: id,
+                    task_id: None,
+                    snippet: highlight(&name, text),
+                    title: name,
+                    score: row.try_get::<f64, _>("score")? as f32,
+                });
+            }
+        }
+
+        if wants(DocKind::Task) {
+            let rows = sqlx::query(
+                "SELECT t.id, t.project_id, t.title, t.description,
+                    MATCH (t.title, t.description) AGAINST (?) AS score
+                 FROM tasks t JOIN projects p ON p.id = t.project_id
+                 WHERE t.user_id = ? AND t.deleted_at IS NULL AND p.deleted_at IS NULL
+                    AND MATCH (t.title, t.description) AGAINST (?)
+                    AND (? IS NULL OR t.project_id = ?)
+                    AND (? IS NULL OR t.status = ?)
+                    AND (? IS NULL OR t.priority = ?)
+                    AND (? OR p.archived_at IS NULL)
+                 ORDER BY score DESC, t.id LIMIT ?",
+            )
+            .bind(text)
+            .bind(query.user_id)
+            .bind(text)
+            .bind(query.project_id)
+            .bind(query.project_id)
+            .bind(status)
+            .bind(status)
+            .bind(priority)
+            .bind(priority)
+            .bind(query.include_archived)
+            .bind(limit)
+            .fetch_all(&self.db_pool)
+            .await?;
+            for row in rows {
+                let id: i64 = row.try_get("id")?;
+                let title: String = row.try_get("title")?;
+                let description: Option<String> = row.try_get("description")?;
+                hits.push(SearchHit {
+                    kind: DocKind::Task,
+                    id,
+                    project_id: row.try_get("project_id")?,
+                    task_id: Some(id),
+                    snippet: snippet(&title, description.as_deref().unwrap_or(""), text),
+                    title,
+                    score: row.try_get::<f64, _>("score")? as f32,
+                });
+            }
+        }
+
+        if wants(DocKind::Comment) {
+            let rows = sqlx::query(
+                "SELECT c.id, c.task_id, t.project_id, t.title, c.body,
+                    MATCH (c.body) AGAINST (?) AS score
+                 FROM task_comments c JOIN tasks t ON t.id = c.task_id JOIN projects p ON p.id = t.project_id
+                 WHERE p.user_id = ? AND c.deleted_at IS NULL AND t.deleted_at IS NULL AND p.deleted_at IS NULL
+                    AND MATCH (c.body) AGAINST (?)
+                    AND (? IS NULL OR t.project_id = ?)
+                    AND (? IS NULL OR t.status = ?)
+                    AND (? IS NULL OR t.priority = ?)
+                    AND (? OR p.archived_at IS NULL)
+                 ORDER BY score DESC, c.id LIMIT ?",
+            )
+            .bind(text)
+            .bind(query.user_id)
+            .bind(text)
+            .bind(query.project_id)
+            .bind(query.project_id)
+            .bind(status)
+            .bind(status)
+            .bind(priority)
+            .bind(priority)
+            .bind(query.include_archived)
+            .bind(limit)
+            .fetch_all(&self.db_pool)
+            .await?;
+            for row in rows {
+                let body: String = row.try_get("body")?;
+                hits.push(SearchHit {
+                    kind: DocKind::Comment,
+                    id: row.try_get("id")?,
+                    project_id: row.try_get("project_id")?,
+                    task_id: Some(row.try_get("task_id")?),
+                    snippet: highlight(&body, text),
+                    title: row.try_get("title")?,
+                    score: row.try_get::<f64, _>("score")? as f32,
+                });
+            }
+        }
+
+        hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+        Ok(hits
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit)
+            .collect())
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+};
+
+use rocket::tokio::{fs, io::AsyncRead};
+
+use super::Storage;
+
+/// Stores blobs on the local filesystem as `<root>/<first two chars of key>/<key>`.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        LocalStorage {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        // Keys are hex digests, anything else could escape the root directory
+        if key.len() < 2 || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid key"));
+        }
+        Ok(self.root.join(&key[..2]).join(key))
+    }
+}
+
+#[rocket::async_trait]
+impl Storage for LocalStorage {
+    async fn exists(&self, key: &str) -> io::Result<bool> {
+        fs::try_exists(self.path(key)?).await
+    }
+
+    async fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path(key)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        // Write next to the target and rename so readers never see a partial file
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes).await?;
+        fs::rename(&tmp, &path).await
+    }
+
+    async fn open(&self, key: &str) -> io::Result<Pin<Box<dyn AsyncRead + Send>>> {
+        let file = fs::File::open(self.path(key)?).await?;
+        Ok(Box::pin(file))
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
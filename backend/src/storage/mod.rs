@@ -0,0 +1,54 @@
+use std::{env, io, pin::Pin, sync::Arc};
+
+use rocket::tokio::io::AsyncRead;
+
+use self::local::LocalStorage;
+
+pub mod local;
+
+/// Content-addressed blob store for attachments, keyed by the SHA-256 of the content.
+#[rocket::async_trait]
+pub trait Storage: Send + Sync {
+    async fn exists(&self, key: &str) -> io::Result<bool>;
+    /// Stores `bytes` under `key`, replacing any previous content.
+    async fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+    async fn open(&self, key: &str) -> io::Result<Pin<Box<dyn AsyncRead + Send>>>;
+    async fn delete(&self, key: &str) -> io::Result<()>;
+}
+
+/// Shared so background jobs can clean up blobs next to the request handlers.
+pub type FileStorage = Arc<dyn Storage>;
+
+pub fn storage_from_env() -> FileStorage {
+    let root = env::var("ATTACHMENTS_DIR").unwrap_or_else(|_| "attachments".to_string());
+    Arc::new(LocalStorage::new(root))
+}
+
+pub struct AttachmentLimits {
+    pub max_bytes: u64,
+    pub allowed_types: Vec<String>,
+}
+
+impl AttachmentLimits {
+    /// Reads `ATTACHMENT_MAX_BYTES` (default 10 MiB) and `ATTACHMENT_TYPES`, a comma separated
+    /// list of allowed MIME types.
+    pub fn from_env() -> Self {
+        let max_bytes = env::var("ATTACHMENT_MAX_BYTES")
+            .ok()
+            .and_then(|bytes| bytes.parse().ok())
+            .unwrap_or(10 * 1024 * 1024);
+        let allowed_types = env::var("ATTACHMENT_TYPES")
+            .unwrap_or_else(|_| {
+                "image/png,image/jpeg,image/gif,image/webp,application/pdf,text/plain,text/markdown"
+                    .to_string()
+            })
+            .split(',')
+            .map(|mime| mime.trim().to_ascii_lowercase())
+            .filter(|mime| !mime.is_empty())
+            .collect();
+        AttachmentLimits {
+            max_bytes,
+            allowed_types,
+        }
+    }
+}
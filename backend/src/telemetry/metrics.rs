@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use prometheus::{
+    core::Collector, Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    Opts, Registry, TextEncoder,
+};
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    Request, Response,
+};
+
+use super::request::RequestTrace;
+
+/// Connection counts of a database pool, read at scrape time.
+pub trait PoolStats: Send + Sync {
+    fn connections(&self) -> u32;
+    fn idle(&self) -> usize;
+    fn max_connections(&self) -> u32;
+}
+
+impl<D: sqlx::Database> PoolStats for sqlx::Pool<D> {
+    fn connections(&self) -> u32 {
+        self.size()
+    }
+
+    fn idle(&self) -> usize {
+        self.num_idle()
+    }
+
+    fn max_connections(&self) -> u32 {
+        self.options().get_max_connections()
+    }
+}
+
+/// Every metric of the server, cheap to clone as the collectors are shared.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    pool: Arc<dyn PoolStats>,
+    requests: IntCounterVec,
+    latency: HistogramVec,
+    pool_connections: IntGauge,
+    pool_idle: IntGauge,
+    pool_max: IntGauge,
+    /// Queries that gave up waiting for a free connection, sqlx does not report
+    /// the waits themselves.
+    pub pool_timeouts: IntCounter,
+    pub sign_ups: IntCounter,
+    pub sign_ins: IntCounter,
+    pub failed_logins: IntCounter,
+    pub tasks_created: IntCounter,
+}
+
+fn register<C: Collector + Clone + 'static>(registry: &Registry, collector: C) -> C {
+    registry
+        .register(Box::new(collector.clone()))
+        .expect("metric registered twice");
+    collector
+}
+
+fn counter(registry: &Registry, name: &str, help: &str) -> IntCounter {
+    register(
+        registry,
+        IntCounter::new(name, help).expect("invalid metric"),
+    )
+}
+
+fn gauge(registry: &Registry, name: &str, help: &str) -> IntGauge {
+    register(registry, IntGauge::new(name, help).expect("invalid metric"))
+}
+
+impl Metrics {
+    pub fn new(pool: impl PoolStats + 'static) -> Self {
+        let registry = Registry::new();
+        let requests = IntCounterVec::new(
+            Opts::new(
+                "http_requests_total",
+                "HTTP requests by method, route and status",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("invalid metric");
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time from receiving a request to sending the response headers",
+            ),
+            &["method", "route"],
+        )
+        .expect("invalid metric");
+
+        Metrics {
+            requests: register(&registry, requests),
+            latency: register(&registry, latency),
+            pool_connections: gauge(
+                &registry,
+                "db_pool_connections",
+                "Open database connections",
+            ),
+            pool_idle: gauge(
+                &registry,
+                "db_pool_idle_connections",
+                "Idle database connections",
+            ),
+            pool_max: gauge(
+                &registry,
+                "db_pool_max_connections",
+                "Size limit of the database pool",
+            ),
+            pool_timeouts: counter(
+                &registry,
+                "db_pool_timeouts_total",
+                "Queries that timed out waiting for a database connection",
+            ),
+            sign_ups: counter(&registry, "sign_ups_total", "Accounts created"),
+            sign_ins: counter(&registry, "sign_ins_total", "Successful sign-ins"),
+            failed_logins: counter(
+                &registry,
+                "failed_logins_total",
+                "Sign-ins rejected for a wrong email or password",
+            ),
+            tasks_created: counter(&registry, "tasks_created_total", "Tasks created"),
+            pool: Arc::new(pool),
+            registry,
+        }
+    }
+
+    /// Every metric in the Prometheus text format.
+    pub fn render(&self) -> String {
+        self.pool_connections.set(self.pool.connections() as i64);
+        self.pool_idle.set(self.pool.idle() as i64);
+        self.pool_max.set(self.pool.max_connections() as i64);
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("failed to encode metrics");
+        String::from_utf8(buffer).expect("metrics are UTF-8")
+    }
+}
+
+/// Counts the responses and their latency per route. Requests no route matched are
+/// grouped under `unmatched` so probing random paths cannot grow the label set.
+pub struct RequestMetrics;
+
+#[rocket::async_trait]
+impl Fairing for RequestMetrics {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request metrics",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Some(metrics) = request.rocket().state::<Metrics>() else {
+            return;
+        };
+        let method = request.method().as_str();
+        let route = request
+            .route()
+            .map_or_else(|| "unmatched".to_string(), |route| route.uri.to_string());
+        let status = response.status().code.to_string();
+        metrics
+            .requests
+            .with_label_values(&[method, &route, &status])
+            .inc();
+        metrics
+            .latency
+            .with_label_values(&[method, &route])
+            .observe(RequestTrace::of(request).elapsed().as_secs_f64());
+    }
+}
@@ -0,0 +1,19 @@
+use std::env;
+
+use tracing_subscriber::EnvFilter;
+
+pub mod metrics;
+pub mod request;
+
+/// Installs the global subscriber, Rocket's and sqlx's own logs included. `RUST_LOG`
+/// takes filter directives such as `info,sqlx::query=debug` (default `info`) and
+/// `LOG_FORMAT=json` prints one JSON object per line instead of plain text.
+pub fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    if env::var("LOG_FORMAT").as_deref() == Ok("json") {
+        builder.json().with_current_span(true).init();
+    } else {
+        builder.init();
+    }
+}
@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    route::{self, Handler},
+    Data, Request, Response, Route,
+};
+use tracing::{field::Empty, Instrument, Span};
+
+use crate::guards::request_id::RequestId;
+
+/// The span every log line of a request is recorded in. `route` and `user_id` are
+/// filled in once known, `status` when the response is ready.
+pub struct RequestTrace {
+    pub span: Span,
+    started: Instant,
+}
+
+impl RequestTrace {
+    pub fn of<'r>(request: &'r Request<'_>) -> &'r RequestTrace {
+        request.local_cache(|| RequestTrace {
+            span: tracing::info_span!(
+                "request",
+                request_id = %RequestId::of(request).0,
+                method = %request.method(),
+                uri = %request.uri(),
+                route = Empty,
+                user_id = Empty,
+                status = Empty,
+            ),
+            started: Instant::now(),
+        })
+    }
+
+    /// Time since the request came in.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+/// Opens the span of each request and logs its outcome, the request id is sent back
+/// in `X-Request-Id`.
+pub struct RequestTracing;
+
+#[rocket::async_trait]
+impl Fairing for RequestTracing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request tracing",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        RequestTrace::of(request);
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        response.set_raw_header("X-Request-Id", RequestId::of(request).0.clone());
+
+        let trace = RequestTrace::of(request);
+        let status = response.status().code;
+        let latency_ms = trace.elapsed().as_secs_f64() * 1000.0;
+        trace.span.record("status", status);
+        trace.span.in_scope(|| {
+            if status >= 500 {
+                tracing::error!(status, latency_ms, "request failed");
+            } else {
+                tracing::info!(status, latency_ms, "request finished");
+            }
+        });
+    }
+}
+
+/// Runs a route handler, request guards included, inside the span of its request.
+#[derive(Clone)]
+struct Traced(Box<dyn Handler>);
+
+#[rocket::async_trait]
+impl Handler for Traced {
+    async fn handle<'r>(&self, request: &'r Request<'_>, data: Data<'r>) -> route::Outcome<'r> {
+        let span = RequestTrace::of(request).span.clone();
+        if let Some(route) = request.route() {
+            span.record("route", tracing::field::display(&route.uri));
+        }
+        self.0.handle(request, data).instrument(span).await
+    }
+}
+
+/// Wraps the handlers of `routes` so their logs and queries land in the request span.
+pub fn traced(routes: Vec<Route>) -> Vec<Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(Traced(route.handler));
+            route
+        })
+        .collect()
+}
@@ -0,0 +1,877 @@
+use std::fmt;
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+use crate::models::tasks::{TaskPriority, TaskStatus};
+
+/// Longest accepted query, in characters.
+pub const MAX_QUERY_LEN: usize = 1000;
+/// Deepest accepted nesting of parentheses and negations.
+const MAX_DEPTH: usize = 32;
+
+/// A query that could not be parsed, `position` is the character offset of the
+/// offending input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterError {
+    pub position: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+fn error<T>(position: usize, message: &'static str) -> Result<T, FilterError> {
+    Err(FilterError { position, message })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Comparison {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            Comparison::Eq => "=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DueValue {
+    /// A calendar day (UTC), `due:` matches the whole day.
+    Date(NaiveDate),
+    /// An offset from the time the query runs, e.g. `7d`, `-2w`, `12h`.
+    Relative(Duration),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Condition {
+    /// Project name, case-insensitive.
+    Project(String),
+    Label(String),
+    Status(TaskStatus),
+    Priority(Comparison, TaskPriority),
+    Due(Comparison, DueValue),
+    /// `due:none`, tasks without a due date.
+    NoDueDate,
+    Points(Comparison, i64),
+    /// Substring of the title.
+    Title(String),
+    /// Substring of the title or description, for words without a field.
+    Text(String),
+}
+
+/// Parsed filter query. Terms next to each other must all match, `OR` binds
+/// looser than that, `-` negates a term or a parenthesized group.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Filter {
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    Condition(Condition),
+}
+
+/// Rank of `t.priority` from 1 (low) to 4 (urgent), portable across databases.
+pub const PRIORITY_RANK: &str =
+    "CASE t.priority WHEN 'low' THEN 1 WHEN 'medium' THEN 2 WHEN 'high' THEN 3 WHEN 'urgent' THEN 4 ELSE 0 END";
+
+/// Value bound to a `?` placeholder of [`SqlFilter::sql`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum SqlParam {
+    Int(i64),
+    Text(String),
+    DateTime(DateTime<Utc>),
+}
+
+/// A `WHERE` fragment over `tasks t JOIN projects p ON p.id = t.project_id`.
+/// User input only ever ends up in `params`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SqlFilter {
+    pub sql: String,
+    pub params: Vec<SqlParam>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    LParen,
+    RParen,
+    Minus,
+    Or,
+    And,
+    Term(Term),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct Term {
+    /// Field name and its position, `None` for a bare word.
+    field: Option<(String, usize)>,
+    comparison: Comparison,
+    comparison_at: usize,
+    value: String,
+    value_at: usize,
+}
+
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || c == '(' || c == ')'
+}
+
+fn tokenize(input: &[char]) -> Result<Vec<(Token, usize)>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < input.len() {
+        let c = input[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        match c {
+            '(' => {
+                tokens.push((Token::LParen, start));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((Token::RParen, start));
+                i += 1;
+            }
+            '-' if input.get(i + 1).is_some_and(|next| !next.is_whitespace()) => {
+                tokens.push((Token::Minus, start));
+                i += 1;
+            }
+            _ => {
+                let (term, end) = read_term(input, i)?;
+                let keyword = term.field.is_none() && input[start] != '"';
+                let token = match term.value.as_str() {
+                    "OR" if keyword => Token::Or,
+                    "AND" if keyword => Token::And,
+                    _ => Token::Term(term),
+                };
+                tokens.push((token, start));
+                i = end;
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn read_term(input: &[char], start: usize) -> Result<(Term, usize), FilterError> {
+    let mut i = start;
+    while i < input.len() && (input[i].is_ascii_alphabetic() || input[i] == '_') {
+        i += 1;
+    }
+    let operator = match (input.get(i), input.get(i + 1)) {
+        _ if i == start => None,
+        (Some(':'), _) | (Some('='), _) => Some((Comparison::Eq, 1)),
+        (Some('<'), Some('=')) => Some((Comparison::Le, 2)),
+        (Some('>'), Some('=')) => Some((Comparison::Ge, 2)),
+        (Some('<'), _) => Some((Comparison::Lt, 1)),
+        (Some('>'), _) => Some((Comparison::Gt, 1)),
+        _ => None,
+    };
+
+    let (field, comparison, comparison_at, value_at) = match operator {
+        Some((comparison, len)) => {
+            let name: String = input[start..i].iter().collect();
+            (
+                Some((name.to_ascii_lowercase(), start)),
+                comparison,
+                i,
+                i + len,
+            )
+        }
+        None => (None, Comparison::Eq, start, start),
+    };
+
+    let (value, end) = read_value(input, value_at)?;
+    if value.is_empty() {
+        return error(value_at, "Missing value");
+    }
+    Ok((
+        Term {
+            field,
+            comparison,
+            comparison_at,
+            value,
+            value_at,
+        },
+        end,
+    ))
+}
+
+/// Reads a plain or double-quoted value, returns it with the offset after it.
+fn read_value(input: &[char], start: usize) -> Result<(String, usize), FilterError> {
+    if input.get(start) == Some(&'"') {
+        let mut value = String::new();
+        let mut i = start + 1;
+        loop {
+            match input.get(i) {
+                None => return error(start, "Unterminated quote"),
+                Some('"') => return Ok((value, i + 1)),
+                Some('\\') if matches!(input.get(i + 1), Some('"') | Some('\\')) => {
+                    value.push(input[i + 1]);
+                    i += 2;
+                }
+                Some(&c) => {
+                    value.push(c);
+                    i += 1;
+                }
+            }
+        }
+    }
+    let mut i = start;
+    while i < input.len() && !is_separator(input[i]) {
+        if input[i] == '"' {
+            return error(i, "Unexpected quote");
+        }
+        i += 1;
+    }
+    Ok((input[start..i].iter().collect(), i))
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+    /// Offset reported for errors at the end of the input.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(token, _)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.end, |(_, position)| *position)
+    }
+
+    fn or_expr(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        let mut alternatives = vec![self.and_expr(depth)?];
+        while self.peek() == Some(&Token::Or) {
+            self.next += 1;
+            alternatives.push(self.and_expr(depth)?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            Filter::Or(alternatives)
+        })
+    }
+
+    fn and_expr(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        let mut terms = vec![self.unary(depth)?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next += 1;
+                }
+                Some(Token::Minus) | Some(Token::LParen) | Some(Token::Term(_)) => {}
+                _ => break,
+            }
+            terms.push(self.unary(depth)?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Filter::And(terms)
+        })
+    }
+
+    fn unary(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        if depth > MAX_DEPTH {
+            return error(self.position(), "Query is nested too deeply");
+        }
+        let position = self.position();
+        match self.tokens.get(self.next).map(|(token, _)| token.clone()) {
+            Some(Token::Minus) => {
+                self.next += 1;
+                Ok(Filter::Not(Box::new(self.unary(depth + 1)?)))
+            }
+            Some(Token::LParen) => {
+                self.next += 1;
+                let inner = self.or_expr(depth + 1)?;
+                if self.peek() != Some(&Token::RParen) {
+                    return error(self.position(), "Expected )");
+                }
+                self.next += 1;
+                Ok(inner)
+            }
+            Some(Token::Term(term)) => {
+                self.next += 1;
+                Ok(Filter::Condition(condition(term)?))
+            }
+            Some(Token::RParen) => error(position, "Unexpected )"),
+            Some(Token::Or) | Some(Token::And) => error(position, "Expected a term"),
+            None => error(position, "Unexpected end of query"),
+        }
+    }
+}
+
+fn equality_only(term: &Term) -> Result<(), FilterError> {
+    if term.comparison != Comparison::Eq {
+        return error(term.comparison_at, "This field only supports :");
+    }
+    Ok(())
+}
+
+fn condition(term: Term) -> Result<Condition, FilterError> {
+    let Some((field, field_at)) = term.field.clone() else {
+        return Ok(Condition::Text(term.value));
+    };
+    let value = term.value.as_str();
+    match field.as_str() {
+        "project" => {
+            equality_only(&term)?;
+            Ok(Condition::Project(term.value))
+        }
+        "label" => {
+            equality_only(&term)?;
+            Ok(Condition::Label(value.to_lowercase()))
+        }
+        "title" => {
+            equality_only(&term)?;
+            Ok(Condition::Title(term.value))
+        }
+        "status" => {
+            equality_only(&term)?;
+            match value.to_ascii_lowercase().parse() {
+                Ok(status) => Ok(Condition::Status(status)),
+                Err(()) => error(term.value_at, "Expected todo, in_progress or done"),
+            }
+        }
+        "priority" => match value.to_ascii_lowercase().parse() {
+            Ok(priority) => Ok(Condition::Priority(term.comparison, priority)),
+            Err(()) => error(term.value_at, "Expected low, medium, high or urgent"),
+        },
+        "points" => match value.parse() {
+            Ok(points) => Ok(Condition::Points(term.comparison, points)),
+            Err(_) => error(term.value_at, "Expected a whole number"),
+        },
+        "due" => {
+            if value.eq_ignore_ascii_case("none") {
+                equality_only(&term)?;
+                return Ok(Condition::NoDueDate);
+            }
+            let due = parse_due(value).ok_or(FilterError {
+                position: term.value_at,
+                message: "Expected a date (YYYY-MM-DD), today, none or an offset like 7d",
+            })?;
+            if matches!(due, DueValue::Relative(_)) && term.comparison == Comparison::Eq {
+                return error(term.comparison_at, "Use <, <=, > or >= with an offset");
+            }
+            Ok(Condition::Due(term.comparison, due))
+        }
+        _ => error(field_at, "Unknown field"),
+    }
+}
+
+fn parse_due(value: &str) -> Option<DueValue> {
+    if value.eq_ignore_ascii_case("today") {
+        return Some(DueValue::Date(Utc::now().date_naive()));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(DueValue::Date(date));
+    }
+    let unit = value.chars().last()?;
+    let amount: i64 = value[..value.len() - unit.len_utf8()].parse().ok()?;
+    let offset = match unit.to_ascii_lowercase() {
+        'h' => Duration::try_hours(amount),
+        'd' => Duration::try_days(amount),
+        'w' => Duration::try_weeks(amount),
+        _ => None,
+    }?;
+    // Keeps `now + offset` far from the limits of `DateTime`
+    if offset.num_days().abs() > 100 * 366 {
+        return None;
+    }
+    Some(DueValue::Relative(offset))
+}
+
+/// Parses a filter query, an empty query matches everything.
+pub fn parse(query: &str) -> Result<Filter, FilterError> {
+    let input: Vec<char> = query.chars().collect();
+    if input.len() > MAX_QUERY_LEN {
+        return error(MAX_QUERY_LEN, "Query is too long");
+    }
+    let tokens = tokenize(&input)?;
+    if tokens.is_empty() {
+        return Ok(Filter::And(Vec::new()));
+    }
+    let mut parser = Parser {
+        tokens,
+        next: 0,
+        end: input.len(),
+    };
+    let filter = parser.or_expr(0)?;
+    if parser.next < parser.tokens.len() {
+        return error(parser.position(), "Unexpected )");
+    }
+    Ok(filter)
+}
+
+/// Substring pattern for `LIKE ? ESCAPE '!'`, the escape character is the same
+/// on every database.
+fn like_pattern(value: &str) -> String {
+    let mut pattern = String::with_capacity(value.len() + 2);
+    pattern.push('%');
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '!') {
+            pattern.push('!');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight"))
+}
+
+impl Filter {
+    /// Compiles the filter, relative dates are resolved against `now`. Every
+    /// condition is two-valued (never NULL), so negating one is well defined.
+    pub fn to_sql(&self, now: DateTime<Utc>) -> SqlFilter {
+        let mut compiled = SqlFilter {
+            sql: String::new(),
+            params: Vec::new(),
+        };
+        self.write_sql(now, &mut compiled);
+        compiled
+    }
+
+    fn write_sql(&self, now: DateTime<Utc>, out: &mut SqlFilter) {
+        match self {
+            Filter::And(filters) | Filter::Or(filters) if filters.is_empty() => {
+                out.sql.push_str(if matches!(self, Filter::And(_)) {
+                    "TRUE"
+                } else {
+                    "FALSE"
+                });
+            }
+            Filter::And(filters) | Filter::Or(filters) => {
+                let joiner = if matches!(self, Filter::And(_)) {
+                    " AND "
+                } else {
+                    " OR "
+                };
+                out.sql.push('(');
+                for (i, filter) in filters.iter().enumerate() {
+                    if i > 0 {
+                        out.sql.push_str(joiner);
+                    }
+                    filter.write_sql(now, out);
+                }
+                out.sql.push(')');
+            }
+            Filter::Not(filter) => {
+                out.sql.push_str("NOT (");
+                filter.write_sql(now, out);
+                out.sql.push(')');
+            }
+            Filter::Condition(condition) => condition.write_sql(now, out),
+        }
+    }
+}
+
+impl Condition {
+    fn write_sql(&self, now: DateTime<Utc>, out: &mut SqlFilter) {
+        let (sql, params) = match self {
+            Condition::Project(name) => (
+                "LOWER(p.name) = LOWER(?)".to_string(),
+                vec![SqlParam::Text(name.clone())],
+            ),
+            Condition::Label(label) => (
+                "EXISTS (SELECT 1 FROM task_labels l WHERE l.task_id = t.id AND l.label = ?)"
+                    .to_string(),
+                vec![SqlParam::Text(label.clone())],
+            ),
+            Condition::Status(status) => (
+                "t.status = ?".to_string(),
+                vec![SqlParam::Text(status.as_str().to_string())],
+            ),
+            Condition::Priority(comparison, priority) => (
+                format!("{} {} ?", PRIORITY_RANK, comparison.as_sql()),
+                vec![SqlParam::Int(*priority as i64 + 1)],
+            ),
+            Condition::Due(comparison, DueValue::Relative(offset)) => (
+                format!(
+                    "(t.due_date IS NOT NULL AND t.due_date {} ?)",
+                    comparison.as_sql()
+                ),
+                vec![SqlParam::DateTime(now + *offset)],
+            ),
+            Condition::Due(comparison, DueValue::Date(date)) => {
+                let day = start_of_day(*date);
+                let next_day = day + Duration::days(1);
+                match comparison {
+                    Comparison::Eq => (
+                        "(t.due_date IS NOT NULL AND t.due_date >= ? AND t.due_date < ?)"
+                            .to_string(),
+                        vec![SqlParam::DateTime(day), SqlParam::DateTime(next_day)],
+                    ),
+                    Comparison::Lt | Comparison::Le => (
+                        "(t.due_date IS NOT NULL AND t.due_date < ?)".to_string(),
+                        vec![SqlParam::DateTime(if *comparison == Comparison::Lt {
+                            day
+                        } else {
+                            next_day
+                        })],
+                    ),
+                    Comparison::Gt | Comparison::Ge => (
+                        "(t.due_date IS NOT NULL AND t.due_date >= ?)".to_string(),
+                        vec![SqlParam::DateTime(if *comparison == Comparison::Gt {
+                            next_day
+                        } else {
+                            day
+                        })],
+                    ),
+                }
+            }
+            Condition::NoDueDate => ("t.due_date IS NULL".to_string(), Vec::new()),
+            Condition::Points(comparison, points) => (
+                format!(
+                    "(t.story_points IS NOT NULL AND t.story_points {} ?)",
+                    comparison.as_sql()
+                ),
+                vec![SqlParam::Int(*points)],
+            ),
+            Condition::Title(text) => (
+                "LOWER(t.title) LIKE LOWER(?) ESCAPE '!'".to_string(),
+                vec![SqlParam::Text(like_pattern(text))],
+            ),
+            Condition::Text(text) => (
+                "(LOWER(t.title) LIKE LOWER(?) ESCAPE '!' OR LOWER(COALESCE(t.description, '')) LIKE LOWER(?) ESCAPE '!')"
+                    .to_string(),
+                vec![
+                    SqlParam::Text(like_pattern(text)),
+                    SqlParam::Text(like_pattern(text)),
+                ],
+            ),
+        };
+        out.sql.push_str(&sql);
+        out.params.extend(params);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap()
+    }
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, d, 0, 0, 0).unwrap()
+    }
+
+    fn compile(query: &str) -> SqlFilter {
+        parse(query).unwrap().to_sql(now())
+    }
+
+    fn condition(query: &str) -> Condition {
+        match parse(query).unwrap() {
+            Filter::Condition(condition) => condition,
+            other => panic!("{query} parsed to {other:?}"),
+        }
+    }
+
+    fn text(value: &str) -> Filter {
+        Filter::Condition(Condition::Text(value.to_string()))
+    }
+
+    #[test]
+    fn parses_a_full_query() {
+        assert_eq!(
+            parse("project:Infra label:Bug priority>=high due<7d -status:done").unwrap(),
+            Filter::And(vec![
+                Filter::Condition(Condition::Project("Infra".to_string())),
+                Filter::Condition(Condition::Label("bug".to_string())),
+                Filter::Condition(Condition::Priority(Comparison::Ge, TaskPriority::High)),
+                Filter::Condition(Condition::Due(
+                    Comparison::Lt,
+                    DueValue::Relative(Duration::days(7))
+                )),
+                Filter::Not(Box::new(Filter::Condition(Condition::Status(
+                    TaskStatus::Done
+                )))),
+            ])
+        );
+        assert_eq!(parse("  ").unwrap(), Filter::And(Vec::new()));
+        assert_eq!(compile("").sql, "TRUE");
+    }
+
+    #[test]
+    fn compiles_every_operator() {
+        for (query, operator) in [
+            ("priority:high", "="),
+            ("priority=high", "="),
+            ("priority<high", "<"),
+            ("priority<=high", "<="),
+            ("priority>high", ">"),
+            ("PRIORITY>=HIGH", ">="),
+        ] {
+            let compiled = compile(query);
+            assert_eq!(compiled.sql, format!("{PRIORITY_RANK} {operator} ?"));
+            assert_eq!(compiled.params, vec![SqlParam::Int(3)]);
+        }
+
+        for (query, operator) in [
+            ("points:3", "="),
+            ("points<3", "<"),
+            ("points<=3", "<="),
+            ("points>3", ">"),
+            ("points>=3", ">="),
+        ] {
+            let compiled = compile(query);
+            assert_eq!(
+                compiled.sql,
+                format!("(t.story_points IS NOT NULL AND t.story_points {operator} ?)")
+            );
+            assert_eq!(compiled.params, vec![SqlParam::Int(3)]);
+        }
+
+        let project = compile("project:Infra");
+        assert_eq!(project.sql, "LOWER(p.name) = LOWER(?)");
+        assert_eq!(project.params, vec![SqlParam::Text("Infra".to_string())]);
+
+        let label = compile("label:Bug");
+        assert_eq!(
+            label.sql,
+            "EXISTS (SELECT 1 FROM task_labels l WHERE l.task_id = t.id AND l.label = ?)"
+        );
+        assert_eq!(label.params, vec![SqlParam::Text("bug".to_string())]);
+
+        let status = compile("status:IN_PROGRESS");
+        assert_eq!(status.sql, "t.status = ?");
+        assert_eq!(
+            status.params,
+            vec![SqlParam::Text("in_progress".to_string())]
+        );
+
+        let title = compile("title:Deploy");
+        assert_eq!(title.sql, "LOWER(t.title) LIKE LOWER(?) ESCAPE '!'");
+        assert_eq!(title.params, vec![SqlParam::Text("%Deploy%".to_string())]);
+
+        let text = compile("cluster");
+        assert_eq!(
+            text.sql,
+            "(LOWER(t.title) LIKE LOWER(?) ESCAPE '!' OR LOWER(COALESCE(t.description, '')) LIKE LOWER(?) ESCAPE '!')"
+        );
+        assert_eq!(
+            text.params,
+            vec![
+                SqlParam::Text("%cluster%".to_string()),
+                SqlParam::Text("%cluster%".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn compiles_due_dates() {
+        let within = "(t.due_date IS NOT NULL AND t.due_date >= ? AND t.due_date < ?)";
+        let before = "(t.due_date IS NOT NULL AND t.due_date < ?)";
+        let from = "(t.due_date IS NOT NULL AND t.due_date >= ?)";
+        for (query, sql, params) in [
+            ("due:2024-03-10", within, vec![day(10), day(11)]),
+            ("due<2024-03-10", before, vec![day(10)]),
+            ("due<=2024-03-10", before, vec![day(11)]),
+            ("due>2024-03-10", from, vec![day(11)]),
+            ("due>=2024-03-10", from, vec![day(10)]),
+        ] {
+            let compiled = compile(query);
+            assert_eq!(compiled.sql, sql, "{query}");
+            assert_eq!(
+                compiled.params,
+                params
+                    .into_iter()
+                    .map(SqlParam::DateTime)
+                    .collect::<Vec<_>>(),
+                "{query}"
+            );
+        }
+
+        let none = compile("due:none");
+        assert_eq!(none.sql, "t.due_date IS NULL");
+        assert!(none.params.is_empty());
+    }
+
+    #[test]
+    fn resolves_relative_dates_against_now() {
+        for (query, offset) in [
+            ("due<7d", Duration::days(7)),
+            ("due<=12H", Duration::hours(12)),
+            ("due>-2w", Duration::weeks(-2)),
+            ("due>=0d", Duration::zero()),
+        ] {
+            assert_eq!(
+                compile(query).params,
+                vec![SqlParam::DateTime(now() + offset)],
+                "{query}"
+            );
+        }
+        assert_eq!(
+            compile("due<7d").sql,
+            "(t.due_date IS NOT NULL AND t.due_date < ?)"
+        );
+        assert_eq!(
+            condition("due:today"),
+            Condition::Due(Comparison::Eq, DueValue::Date(Utc::now().date_naive()))
+        );
+    }
+
+    #[test]
+    fn combines_and_negates() {
+        assert_eq!(
+            parse("a b OR c AND d").unwrap(),
+            Filter::Or(vec![
+                Filter::And(vec![text("a"), text("b")]),
+                Filter::And(vec![text("c"), text("d")]),
+            ])
+        );
+        assert_eq!(
+            parse("-(label:x OR label:y) -z").unwrap(),
+            Filter::And(vec![
+                Filter::Not(Box::new(Filter::Or(vec![
+                    Filter::Condition(Condition::Label("x".to_string())),
+                    Filter::Condition(Condition::Label("y".to_string())),
+                ]))),
+                Filter::Not(Box::new(text("z"))),
+            ])
+        );
+        assert_eq!(
+            parse("--a").unwrap(),
+            Filter::Not(Box::new(Filter::Not(Box::new(text("a")))))
+        );
+        // A lone dash is a word, not a negation
+        assert_eq!(
+            parse("a - b").unwrap(),
+            Filter::And(vec![text("a"), text("-"), text("b")])
+        );
+
+        let compiled = compile("-(status:done OR points>3) due:none");
+        assert_eq!(
+            compiled.sql,
+            "(NOT ((t.status = ? OR (t.story_points IS NOT NULL AND t.story_points > ?))) AND t.due_date IS NULL)"
+        );
+        assert_eq!(
+            compiled.params,
+            vec![SqlParam::Text("done".to_string()), SqlParam::Int(3)]
+        );
+    }
+
+    #[test]
+    fn handles_quotes() {
+        assert_eq!(
+            condition(r#"project:"Site Reliability""#),
+            Condition::Project("Site Reliability".to_string())
+        );
+        assert_eq!(
+            condition(r#"title:"say \"hi\" \\ \n""#),
+            Condition::Title(r#"say "hi" \ \n"#.to_string())
+        );
+        // Quoted keywords and fields are plain words
+        assert_eq!(parse(r#""OR""#).unwrap(), text("OR"));
+        assert_eq!(parse(r#""status:done""#).unwrap(), text("status:done"));
+        assert_eq!(
+            parse(r#"title:"(a OR b)" x"#).unwrap(),
+            Filter::And(vec![
+                Filter::Condition(Condition::Title("(a OR b)".to_string())),
+                text("x"),
+            ])
+        );
+        // LIKE wildcards and the escape character match literally
+        assert_eq!(
+            compile(r#"title:"50%_off!""#).params,
+            vec![SqlParam::Text("%50!%!_off!!%".to_string())]
+        );
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let nested = |opening: &str, depth: usize| {
+            let closing = if opening == "(" { ")" } else { "" };
+            format!("{}a{}", opening.repeat(depth), closing.repeat(depth))
+        };
+        for opening in ["(", "-"] {
+            assert!(parse(&nested(opening, MAX_DEPTH)).is_ok());
+            assert_eq!(
+                parse(&nested(opening, MAX_DEPTH + 1)),
+                Err(FilterError {
+                    position: MAX_DEPTH + 1,
+                    message: "Query is nested too deeply",
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        for (query, position, message) in [
+            ("label:bug colour:red", 10, "Unknown field"),
+            ("status:open", 7, "Expected todo, in_progress or done"),
+            (
+                "a priority:none",
+                11,
+                "Expected low, medium, high or urgent",
+            ),
+            ("points>=many", 8, "Expected a whole number"),
+            (
+                "due:2024-13-01",
+                4,
+                "Expected a date (YYYY-MM-DD), today, none or an offset like 7d",
+            ),
+            (
+                "due<40000d",
+                4,
+                "Expected a date (YYYY-MM-DD), today, none or an offset like 7d",
+            ),
+            ("due:7d", 3, "Use <, <=, > or >= with an offset"),
+            ("label<x", 5, "This field only supports :"),
+            ("due>none", 3, "This field only supports :"),
+            ("priority: high", 9, "Missing value"),
+            (r#"x title:"abc"#, 8, "Unterminated quote"),
+            (r#"ab"c"#, 2, "Unexpected quote"),
+            ("a OR", 4, "Unexpected end of query"),
+            ("(a b", 4, "Expected )"),
+            ("a) b", 1, "Unexpected )"),
+            (")", 0, "Unexpected )"),
+            ("OR a", 0, "Expected a term"),
+            ("a AND OR b", 6, "Expected a term"),
+            // Offsets count characters, not bytes
+            ("été colour:x", 4, "Unknown field"),
+        ] {
+            assert_eq!(
+                parse(query),
+                Err(FilterError { position, message }),
+                "{query}"
+            );
+        }
+        assert_eq!(
+            parse(&"a".repeat(MAX_QUERY_LEN + 1)),
+            Err(FilterError {
+                position: MAX_QUERY_LEN,
+                message: "Query is too long",
+            })
+        );
+        assert!(parse(&"a".repeat(MAX_QUERY_LEN)).is_ok());
+        assert_eq!(
+            parse("x:1").unwrap_err().to_string(),
+            "Unknown field at position 0"
+        );
+    }
+}
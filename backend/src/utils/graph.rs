@@ -0,0 +1,169 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use chrono::{DateTime, Utc};
+
+pub struct Schedule {
+    pub order: Vec<i64>,
+    pub critical_path: Vec<i64>,
+}
+
+/// Orders tasks so that every task comes after the tasks it depends on.
+///
+/// `nodes` are `(task_id, due_date)` pairs and `edges` are `(task_id, depends_on_id)`
+/// pairs; edges pointing outside of `nodes` are ignored. Among the tasks that are ready
+/// at the same time the earliest due date goes first, tasks without one go last.
+/// The critical path is the longest chain of dependent tasks, on a tie the chain
+/// that has to be finished first wins. Returns `None` if the graph has a cycle.
+pub fn schedule(nodes: &[(i64, Option<DateTime<Utc>>)], edges: &[(i64, i64)]) -> Option<Schedule> {
+    let due_dates: HashMap<i64, Option<DateTime<Utc>>> = nodes.iter().copied().collect();
+    let mut dependents: HashMap<i64, Vec<i64>> = HashMap::new();
+    let mut dependencies: HashMap<i64, Vec<i64>> = HashMap::new();
+    let mut pending: HashMap<i64, usize> = nodes.iter().map(|(id, _)| (*id, 0)).collect();
+
+    for &(task_id, depends_on_id) in edges {
+        if !due_dates.contains_key(&task_id) || !due_dates.contains_key(&depends_on_id) {
+            continue;
+        }
+        dependents.entry(depends_on_id).or_default().push(task_id);
+        dependencies.entry(task_id).or_default().push(depends_on_id);
+        *pending.entry(task_id).or_default() += 1;
+    }
+
+    // `None` sorts before `Some` so it is mapped to `DateTime::MAX_UTC` to go last
+    let priority = |id: i64| Reverse((due_dates[&id].unwrap_or(DateTime::<Utc>::MAX_UTC), id));
+
+    // Kahn's algorithm with the ready tasks kept in due date order
+    let mut ready: BinaryHeap<_> = pending
+        .iter()
+        .filter(|(_, count)| **count == 0)
+        .map(|(id, _)| priority(*id))
+        .collect();
+    let mut order = Vec::with_capacity(nodes.len());
+    while let Some(Reverse((_, id))) = ready.pop() {
+        order.push(id);
+        for dependent in dependents.get(&id).into_iter().flatten() {
+            let count = pending.get_mut(dependent).expect("known task");
+            *count -= 1;
+            if *count == 0 {
+                ready.push(priority(*dependent));
+            }
+        }
+    }
+    if order.len() != nodes.len() {
+        return None;
+    }
+
+    // Longest chain ending at each task, walking the tasks in topological order
+    let mut length: HashMap<i64, usize> = HashMap::new();
+    let mut previous: HashMap<i64, i64> = HashMap::new();
+    for &id in &order {
+        let best = dependencies
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .max_by_key(|dep| (length[*dep], priority(**dep)));
+        match best {
+            Some(dep) => {
+                length.insert(id, length[dep] + 1);
+                previous.insert(id, *dep);
+            }
+            None => {
+                length.insert(id, 1);
+            }
+        }
+    }
+
+    let mut critical_path = Vec::new();
+    let mut cursor = order
+        .iter()
+        .copied()
+        .max_by_key(|id| (length[id], priority(*id)));
+    while let Some(id) = cursor {
+        critical_path.push(id);
+        cursor = previous.get(&id).copied();
+    }
+    critical_path.reverse();
+
+    Some(Schedule {
+        order,
+        critical_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn due(day: u32) -> Option<DateTime<Utc>> {
+        Some(Utc.with_ymd_and_hms(2024, 1, day, 9, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn orders_dependencies_first() {
+        // 3 depends on 2 which depends on 1
+        let schedule = schedule(&[(3, None), (2, None), (1, None)], &[(3, 2), (2, 1)]).unwrap();
+        assert_eq!(schedule.order, vec![1, 2, 3]);
+        assert_eq!(schedule.critical_path, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn breaks_ties_by_due_date_then_id() {
+        let nodes = [(1, None), (2, due(5)), (3, due(2)), (4, None), (5, due(2))];
+        let schedule = schedule(&nodes, &[]).unwrap();
+        assert_eq!(schedule.order, vec![3, 5, 2, 1, 4]);
+        // Every chain has one task, the one due first wins
+        assert_eq!(schedule.critical_path, vec![3]);
+    }
+
+    #[test]
+    fn releases_dependents_in_due_date_order() {
+        // 2 and 3 wait for 1, 4 is free but due last
+        let nodes = [(1, due(1)), (2, due(9)), (3, due(3)), (4, due(5))];
+        let schedule = schedule(&nodes, &[(2, 1), (3, 1)]).unwrap();
+        assert_eq!(schedule.order, vec![1, 3, 4, 2]);
+        assert_eq!(schedule.critical_path, vec![1, 3]);
+    }
+
+    #[test]
+    fn picks_the_longest_chain_across_components() {
+        // Two components: 1 -> 2 and 3 -> 4 -> 5
+        let nodes = [
+            (1, due(1)),
+            (2, due(2)),
+            (3, due(8)),
+            (4, due(9)),
+            (5, None),
+        ];
+        let schedule = schedule(&nodes, &[(2, 1), (4, 3), (5, 4)]).unwrap();
+        assert_eq!(schedule.order, vec![1, 2, 3, 4, 5]);
+        assert_eq!(schedule.critical_path, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn ignores_edges_to_unknown_tasks() {
+        let schedule = schedule(&[(1, None), (2, None)], &[(2, 1), (1, 99), (99, 2)]).unwrap();
+        assert_eq!(schedule.order, vec![1, 2]);
+        assert_eq!(schedule.critical_path, vec![1, 2]);
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        assert!(schedule(&[(1, None), (2, None)], &[(1, 2), (2, 1)]).is_none());
+        // A cycle in one component fails the whole schedule
+        let nodes = [(1, None), (2, None), (3, None), (4, None)];
+        assert!(schedule(&nodes, &[(2, 1), (3, 4), (4, 3)]).is_none());
+        assert!(schedule(&[(1, None)], &[(1, 1)]).is_none());
+    }
+
+    #[test]
+    fn handles_an_empty_graph() {
+        let schedule = schedule(&[], &[]).unwrap();
+        assert!(schedule.order.is_empty());
+        assert!(schedule.critical_path.is_empty());
+    }
+}
@@ -1,33 +1,26 @@
+pub mod filter;
+pub mod graph;
+pub mod rank;
+pub mod rrule;
+pub mod validation;
+
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
-use rocket::{http::Status, response::status, serde::json::Json, time::PrimitiveDateTime};
-
-use crate::{guards::jwt_guard::JwtAuth, models::error::ErrorResponse};
-
-pub fn parse_user_id<'a>(user: JwtAuth) -> Result<i64, status::Custom<Json<ErrorResponse<'a>>>> {
-    user.claims.sub.parse::<i64>().map_err(|_| {
-        status::Custom(
-            Status::Unauthorized,
-            Json(ErrorResponse {
-                error: "Invalid user ID in token",
-            }),
-        )
-    })
+use rocket::time::{OffsetDateTime, PrimitiveDateTime};
+
+use crate::{guards::jwt_guard::JwtAuth, models::error::AppError};
+
+pub fn parse_user_id(user: JwtAuth) -> Result<i64, AppError> {
+    user.claims
+        .sub
+        .parse::<i64>()
+        .map_err(|_| AppError::Unauthorized("Invalid user ID in token"))
 }
 
-pub fn parse_date<'a>(
-    primitive_dt: PrimitiveDateTime,
-) -> Result<DateTime<Utc>, status::Custom<Json<ErrorResponse<'a>>>> {
+pub fn parse_date(primitive_dt: PrimitiveDateTime) -> Result<DateTime<Utc>, AppError> {
     // Convert `rocket::time::Date` to `chrono::NaiveDate`
     let date = primitive_dt.date();
     let naive_date = NaiveDate::from_ymd_opt(date.year(), date.month() as u32, date.day() as u32)
-        .ok_or_else(|| {
-        status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "Database error",
-            }),
-        )
-    })?;
+        .ok_or(AppError::Internal("Database error"))?;
 
     // Convert `rocket::time::Time` to `chrono::NaiveTime`
     let time = primitive_dt.time();
@@ -36,14 +29,7 @@ pub fn parse_date<'a>(
         time.minute() as u32,
         time.second() as u32,
     )
-    .ok_or_else(|| {
-        status::Custom(
-            Status::NotFound,
-            Json(ErrorResponse {
-                error: "Database error",
-            }),
-        )
-    })?;
+    .ok_or(AppError::Internal("Database error"))?;
 
     // Combine `NaiveDate` and `NaiveTime` to create `NaiveDateTime`
     let naive_datetime = NaiveDateTime::new(naive_date, naive_time);
@@ -54,3 +40,9 @@ pub fn parse_date<'a>(
         Utc,
     ))
 }
+
+pub fn to_primitive_date(date_time: DateTime<Utc>) -> Result<PrimitiveDateTime, AppError> {
+    let offset_dt = OffsetDateTime::from_unix_timestamp(date_time.timestamp())
+        .map_err(|_| AppError::BadRequest("date_out_of_range", "Date out of range"))?;
+    Ok(PrimitiveDateTime::new(offset_dt.date(), offset_dt.time()))
+}
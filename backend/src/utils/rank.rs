@@ -0,0 +1,65 @@
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const BASE: usize = DIGITS.len();
+
+/// Ranks longer than this trigger a rebalance of the column.
+pub const MAX_RANK_LEN: usize = 64;
+
+fn digit(c: u8) -> usize {
+    DIGITS.iter().position(|d| *d == c).unwrap_or(0)
+}
+
+/// A rank sorting strictly between `before` and `after`, `None` standing for the
+/// start and the end of the list, so moving an item never renumbers its neighbours.
+/// Generated ranks never end with `0`, which keeps room in front of every rank.
+pub fn rank_between(before: Option<&str>, after: Option<&str>) -> String {
+    let before = before.unwrap_or_default().as_bytes();
+    let mut after = after.map(str::as_bytes);
+    let mut rank = Vec::new();
+
+    for i in 0.. {
+        let lo = before.get(i).map_or(0, |c| digit(*c));
+        let hi = match after.and_then(|after| after.get(i)) {
+            Some(c) => digit(*c),
+            None => {
+                after = None;
+                BASE
+            }
+        };
+        if hi > lo + 1 {
+            rank.push(DIGITS[(lo + hi) / 2]);
+            break;
+        }
+        rank.push(DIGITS[lo]);
+        if hi != lo {
+            // From here on every suffix sorts below `after`
+            after = None;
+        }
+    }
+
+    String::from_utf8(rank).expect("rank digits are ascii")
+}
+
+/// `count` ranks spread evenly over the whole range, used to rebalance a list.
+pub fn spread_ranks(count: usize) -> Vec<String> {
+    let mut width = 1;
+    while BASE.pow(width) <= count {
+        width += 1;
+    }
+    let space = BASE.pow(width);
+
+    (1..=count)
+        .map(|i| {
+            let mut value = i * space / (count + 1);
+            let mut rank = vec![b'0'; width as usize];
+            for slot in rank.iter_mut().rev() {
+                *slot = DIGITS[value % BASE];
+                value /= BASE;
+            }
+            // Trailing zeros do not change the order and would block inserting in front
+            while rank.len() > 1 && rank.last() == Some(&b'0') {
+                rank.pop();
+            }
+            String::from_utf8(rank).expect("rank digits are ascii")
+        })
+        .collect()
+}
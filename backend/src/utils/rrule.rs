@@ -0,0 +1,418 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, Utc, Weekday};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// The subset of RFC 5545 `RRULE` we support: `FREQ` (daily, weekly, monthly),
+/// `INTERVAL`, `BYDAY` (plain weekdays, daily and weekly only) and `UNTIL` or `COUNT`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<Weekday>,
+    pub until: Option<DateTime<Utc>>,
+    pub count: Option<u32>,
+}
+
+/// Largest accepted `INTERVAL`.
+const MAX_INTERVAL: u32 = 1000;
+/// Largest accepted `COUNT`.
+const MAX_COUNT: u32 = 10_000;
+/// Series end before this year, the databases store nothing later.
+const LAST_YEAR: i32 = 9999;
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+fn parse_weekday(value: &str) -> Result<Weekday, &'static str> {
+    WEEKDAYS
+        .iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(value))
+        .map(|(_, day)| *day)
+        .ok_or("BYDAY expects a list of MO, TU, WE, TH, FR, SA, SU")
+}
+
+fn parse_until(value: &str) -> Result<DateTime<Utc>, &'static str> {
+    let value = value.trim_end_matches('Z');
+    if let Ok(date_time) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Ok(date_time.and_utc());
+    }
+    // A plain date includes the whole day
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(23, 59, 59))
+        .map(|date_time| date_time.and_utc())
+        .ok_or("UNTIL expects YYYYMMDD or YYYYMMDDTHHMMSSZ")
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("RRULE:").unwrap_or(s);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut by_day = Vec::new();
+        let mut until = None;
+        let mut count = None;
+        for part in s.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part.split_once('=').ok_or("Malformed RRULE")?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err("FREQ must be DAILY, WEEKLY or MONTHLY"),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| (1..=MAX_INTERVAL).contains(interval))
+                        .ok_or("INTERVAL must be an integer between 1 and 1000")?
+                }
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(parse_weekday)
+                        .collect::<Result<_, _>>()?
+                }
+                "UNTIL" => until = Some(parse_until(value)?),
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|count| (1..=MAX_COUNT).contains(count))
+                            .ok_or("COUNT must be an integer between 1 and 10000")?,
+                    )
+                }
+                _ => return Err("Unsupported RRULE part"),
+            }
+        }
+
+        let frequency = frequency.ok_or("RRULE requires FREQ")?;
+        if until.is_some() && count.is_some() {
+            return Err("UNTIL and COUNT cannot be combined");
+        }
+        if frequency == Frequency::Monthly && !by_day.is_empty() {
+            return Err("BYDAY is not supported with FREQ=MONTHLY");
+        }
+        // Every step would land on the same weekday, so most BYDAY days are never reached
+        if frequency == Frequency::Daily && !by_day.is_empty() && interval % 7 == 0 {
+            return Err("BYDAY with FREQ=DAILY needs an INTERVAL that is not a multiple of 7");
+        }
+        by_day.sort_by_key(|day: &Weekday| day.num_days_from_monday());
+        by_day.dedup();
+
+        Ok(RecurrenceRule {
+            frequency,
+            interval,
+            by_day,
+            until,
+            count,
+        })
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self
+                .by_day
+                .iter()
+                .map(|day| WEEKDAYS[day.num_days_from_monday() as usize].0)
+                .collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%SZ"))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        Ok(())
+    }
+}
+
+impl RecurrenceRule {
+    /// The occurrence following `current`, which is the `occurrence`-th one of the
+    /// series (starting at 1). Returns `None` once the series is over or would
+    /// run past the year 9999.
+    pub fn next_after(&self, current: DateTime<Utc>, occurrence: u32) -> Option<DateTime<Utc>> {
+        if self.count.is_some_and(|count| occurrence >= count) {
+            return None;
+        }
+
+        let next = match self.frequency {
+            Frequency::Daily => {
+                let step = Duration::days(self.interval as i64);
+                let mut candidate = current.checked_add_signed(step)?;
+                if !self.by_day.is_empty() {
+                    // Seven steps are enough to visit every weekday reachable with this interval
+                    let mut steps = 0;
+                    while !self.by_day.contains(&candidate.weekday()) {
+                        steps += 1;
+                        if steps == 7 {
+                            return None;
+                        }
+                        candidate = candidate.checked_add_signed(step)?;
+                    }
+                }
+                candidate
+            }
+            Frequency::Weekly if self.by_day.is_empty() => {
+                current.checked_add_signed(Duration::weeks(self.interval as i64))?
+            }
+            Frequency::Weekly => {
+                // A later day of the same week, otherwise the first day of the next active week
+                let today = current.weekday().num_days_from_monday();
+                match self
+                    .by_day
+                    .iter()
+                    .find(|day| day.num_days_from_monday() > today)
+                {
+                    Some(day) => current.checked_add_signed(Duration::days(
+                        (day.num_days_from_monday() - today) as i64,
+                    ))?,
+                    None => current.checked_add_signed(
+                        Duration::weeks(self.interval as i64)
+                            + Duration::days(
+                                self.by_day[0].num_days_from_monday() as i64 - today as i64,
+                            ),
+                    )?,
+                }
+            }
+            Frequency::Monthly => {
+                // Months that do not have the day (e.g. the 31st) are skipped, as in RFC 5545
+                let date = current.date_naive();
+                (1..=12).find_map(|step| {
+                    date.with_day(1)?
+                        .checked_add_months(Months::new(self.interval.checked_mul(step)?))?
+                        .with_day(date.day())
+                        .map(|next| next.and_time(current.time()).and_utc())
+                })?
+            }
+        };
+
+        match self.until {
+            Some(until) if next > until => None,
+            _ if next.year() > LAST_YEAR => None,
+            _ => Some(next),
+        }
+    }
+
+    /// The first occurrence strictly after `after` following `current`, the
+    /// `occurrence`-th one of the series, along with its number. Occurrences in
+    /// between are skipped but still count towards `COUNT`.
+    pub fn first_after(
+        &self,
+        current: DateTime<Utc>,
+        occurrence: u32,
+        after: DateTime<Utc>,
+    ) -> Option<(DateTime<Utc>, u32)> {
+        let mut current = current;
+        let mut occurrence = occurrence;
+        loop {
+            current = self.next_after(current, occurrence)?;
+            occurrence += 1;
+            if current > after {
+                return Some((current, occurrence));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 9, 0, 0).unwrap()
+    }
+
+    fn rule(s: &str) -> RecurrenceRule {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_and_normalizes() {
+        let parsed = rule("RRULE:freq=weekly;interval=2;byday=fr,MO,mo;count=3");
+        assert_eq!(parsed.frequency, Frequency::Weekly);
+        assert_eq!(parsed.interval, 2);
+        assert_eq!(parsed.by_day, vec![Weekday::Mon, Weekday::Fri]);
+        assert_eq!(parsed.count, Some(3));
+        assert_eq!(
+            parsed.to_string(),
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR;COUNT=3"
+        );
+        assert_eq!(rule(&parsed.to_string()), parsed);
+
+        let until = rule("FREQ=DAILY;UNTIL=20240105");
+        assert_eq!(
+            until.until,
+            Some(Utc.with_ymd_and_hms(2024, 1, 5, 23, 59, 59).unwrap())
+        );
+        assert_eq!(until.to_string(), "FREQ=DAILY;UNTIL=20240105T235959Z");
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        for (input, error) in [
+            ("INTERVAL=2", "RRULE requires FREQ"),
+            ("FREQ=YEARLY", "FREQ must be DAILY, WEEKLY or MONTHLY"),
+            (
+                "FREQ=DAILY;INTERVAL=0",
+                "INTERVAL must be an integer between 1 and 1000",
+            ),
+            (
+                "FREQ=WEEKLY;INTERVAL=1001",
+                "INTERVAL must be an integer between 1 and 1000",
+            ),
+            (
+                "FREQ=DAILY;COUNT=-1",
+                "COUNT must be an integer between 1 and 10000",
+            ),
+            (
+                "FREQ=DAILY;COUNT=10001",
+                "COUNT must be an integer between 1 and 10000",
+            ),
+            (
+                "FREQ=DAILY;BYDAY=XX",
+                "BYDAY expects a list of MO, TU, WE, TH, FR, SA, SU",
+            ),
+            (
+                "FREQ=DAILY;UNTIL=tomorrow",
+                "UNTIL expects YYYYMMDD or YYYYMMDDTHHMMSSZ",
+            ),
+            (
+                "FREQ=DAILY;COUNT=2;UNTIL=20240101",
+                "UNTIL and COUNT cannot be combined",
+            ),
+            (
+                "FREQ=MONTHLY;BYDAY=MO",
+                "BYDAY is not supported with FREQ=MONTHLY",
+            ),
+            (
+                "FREQ=DAILY;INTERVAL=14;BYDAY=MO",
+                "BYDAY with FREQ=DAILY needs an INTERVAL that is not a multiple of 7",
+            ),
+            ("FREQ=DAILY;BYMONTH=1", "Unsupported RRULE part"),
+            ("FREQ", "Malformed RRULE"),
+        ] {
+            assert_eq!(input.parse::<RecurrenceRule>(), Err(error), "{}", input);
+        }
+    }
+
+    #[test]
+    fn steps_by_frequency_and_interval() {
+        assert_eq!(
+            rule("FREQ=DAILY;INTERVAL=3").next_after(at(2024, 1, 30), 1),
+            Some(at(2024, 2, 2))
+        );
+        assert_eq!(
+            rule("FREQ=WEEKLY").next_after(at(2024, 1, 3), 1),
+            Some(at(2024, 1, 10))
+        );
+        assert_eq!(
+            rule("FREQ=MONTHLY;INTERVAL=2").next_after(at(2024, 1, 15), 1),
+            Some(at(2024, 3, 15))
+        );
+        // Months without a 31st are skipped
+        assert_eq!(
+            rule("FREQ=MONTHLY").next_after(at(2024, 1, 31), 1),
+            Some(at(2024, 3, 31))
+        );
+    }
+
+    #[test]
+    fn by_day_picks_the_next_listed_weekday() {
+        // 2024-01-05 is a Friday
+        let weekdays = rule("FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR");
+        assert_eq!(weekdays.next_after(at(2024, 1, 5), 1), Some(at(2024, 1, 8)));
+        assert_eq!(weekdays.next_after(at(2024, 1, 8), 1), Some(at(2024, 1, 9)));
+
+        let every_other_day = rule("FREQ=DAILY;INTERVAL=2;BYDAY=MO");
+        assert_eq!(
+            every_other_day.next_after(at(2024, 1, 5), 1),
+            Some(at(2024, 1, 15))
+        );
+
+        let biweekly = rule("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH");
+        assert_eq!(biweekly.next_after(at(2024, 1, 1), 1), Some(at(2024, 1, 4)));
+        assert_eq!(
+            biweekly.next_after(at(2024, 1, 4), 2),
+            Some(at(2024, 1, 15))
+        );
+    }
+
+    #[test]
+    fn ends_the_series() {
+        let count = rule("FREQ=DAILY;COUNT=3");
+        assert_eq!(count.next_after(at(2024, 1, 1), 2), Some(at(2024, 1, 2)));
+        assert_eq!(count.next_after(at(2024, 1, 2), 3), None);
+
+        let until = rule("FREQ=WEEKLY;UNTIL=20240114");
+        assert_eq!(until.next_after(at(2024, 1, 1), 1), Some(at(2024, 1, 8)));
+        assert_eq!(until.next_after(at(2024, 1, 8), 2), None);
+
+        // The series stops before leaving the supported dates
+        let far = Utc.with_ymd_and_hms(9998, 6, 1, 9, 0, 0).unwrap();
+        for input in [
+            "FREQ=DAILY;INTERVAL=1000",
+            "FREQ=WEEKLY;INTERVAL=1000",
+            "FREQ=WEEKLY;INTERVAL=1000;BYDAY=MO",
+            "FREQ=MONTHLY;INTERVAL=1000",
+        ] {
+            assert_eq!(rule(input).next_after(far, 1), None, "{}", input);
+            assert_eq!(rule(input).next_after(DateTime::<Utc>::MAX_UTC, 1), None);
+        }
+    }
+
+    #[test]
+    fn first_after_skips_missed_occurrences() {
+        let daily = rule("FREQ=DAILY");
+        assert_eq!(
+            daily.first_after(at(2024, 1, 1), 1, at(2024, 1, 10)),
+            Some((at(2024, 1, 11), 11))
+        );
+        // An occurrence in the future is taken as is
+        assert_eq!(
+            daily.first_after(at(2024, 1, 1), 1, at(2023, 1, 1)),
+            Some((at(2024, 1, 2), 2))
+        );
+        // Skipped occurrences count towards COUNT
+        assert_eq!(
+            rule("FREQ=DAILY;COUNT=5").first_after(at(2024, 1, 1), 1, at(2024, 1, 10)),
+            None
+        );
+    }
+}
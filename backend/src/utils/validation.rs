@@ -0,0 +1,138 @@
+use crate::models::error::{AppError, FieldError};
+
+/// Longest value of a `VARCHAR(255)` column, in characters.
+pub const MAX_VARCHAR: usize = 255;
+/// Longest value of a `TEXT` column, in bytes.
+pub const MAX_TEXT_BYTES: usize = 65_535;
+pub const MAX_LABEL: usize = 50;
+pub const MAX_QUERY: usize = 1000;
+
+/// An input model checked and cleaned up before it reaches a handler, see
+/// `guards::valid::Valid`.
+pub trait Validate {
+    fn validate(&mut self, v: &mut Validator);
+}
+
+/// Collects every invalid field of a request instead of stopping at the first one.
+#[derive(Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn error(&mut self, field: &'static str, code: &'static str, message: &'static str) {
+        self.errors.push(FieldError {
+            field,
+            code,
+            message,
+        });
+    }
+
+    /// Adds an error unless `ok` holds.
+    pub fn check(
+        &mut self,
+        ok: bool,
+        field: &'static str,
+        code: &'static str,
+        message: &'static str,
+    ) {
+        if !ok {
+            self.error(field, code, message);
+        }
+    }
+
+    /// A single line of text: trimmed, not empty and at most `max` characters.
+    pub fn text(&mut self, field: &'static str, value: &mut String, max: usize) {
+        trim(value);
+        if value.is_empty() {
+            self.error(field, "required", "Cannot be empty");
+        } else if value.chars().count() > max {
+            self.error(field, "too_long", "Exceeds the maximum length");
+        }
+    }
+
+    /// Like `text`, but an absent or empty value is fine.
+    pub fn optional_text(&mut self, field: &'static str, value: &mut Option<String>, max: usize) {
+        if let Some(value) = value {
+            trim(value);
+            if value.chars().count() > max {
+                self.error(field, "too_long", "Exceeds the maximum length");
+            }
+        }
+    }
+
+    /// Free text such as Markdown, kept as written and only checked against the size of a
+    /// `TEXT` column.
+    pub fn body(&mut self, field: &'static str, value: &str) {
+        if value.trim().is_empty() {
+            self.error(field, "required", "Cannot be empty");
+        } else if value.len() > MAX_TEXT_BYTES {
+            self.error(field, "too_long", "Exceeds the maximum length");
+        }
+    }
+
+    pub fn optional_body(&mut self, field: &'static str, value: Option<&str>) {
+        if value.is_some_and(|value| value.len() > MAX_TEXT_BYTES) {
+            self.error(field, "too_long", "Exceeds the maximum length");
+        }
+    }
+
+    /// An email address of the `local@domain.tld` form, trimmed and lowercased so the
+    /// same address is never stored twice with different case.
+    pub fn email(&mut self, field: &'static str, value: &mut String) {
+        self.text(field, value, MAX_VARCHAR);
+        *value = value.to_lowercase();
+        if !value.is_empty() && !is_email(value) {
+            self.error(field, "invalid_email", "Not a valid email address");
+        }
+    }
+
+    /// Labels are compared case-insensitively and cannot contain whitespace, so
+    /// they can be written as `label:<name>` in filters.
+    pub fn label(&mut self, field: &'static str, value: &mut String) {
+        *value = value.trim().to_lowercase();
+        if value.is_empty()
+            || value.chars().count() > MAX_LABEL
+            || value.contains(char::is_whitespace)
+        {
+            self.error(
+                field,
+                "invalid_label",
+                "Labels must be 1 to 50 characters without spaces",
+            );
+        }
+    }
+
+    pub fn into_errors(self) -> Vec<FieldError> {
+        self.errors
+    }
+
+    pub fn finish(self) -> Result<(), AppError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Validation(self.errors))
+        }
+    }
+}
+
+fn trim(value: &mut String) {
+    let trimmed = value.trim();
+    if trimmed.len() != value.len() {
+        *value = trimmed.to_string();
+    }
+}
+
+/// A deliberately loose check, whether the address exists is up to the mail server.
+fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.rsplit_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !local.contains('@')
+        && !value.chars().any(|c| c.is_whitespace() || c.is_control())
+        && domain.contains('.')
+        && domain
+            .split('.')
+            .all(|part| !part.is_empty() && !part.starts_with('-') && !part.ends_with('-'))
+}